@@ -14,6 +14,7 @@ def count(
     normalize: bool = True,
     stop_words: list[str] = None,
     sort: bool = False,
+    binary: bool = False,
 ) -> dict[str, int]:
     """Count the frequency of words in a text or in a list of texts. Tokens are unordered within
     the same text.
@@ -38,6 +39,12 @@ def count(
     stop_words
         list of str, default=None.
         A list of stop words that will be removed from the text.
+    sort
+        bool, default=False.
+        Whether to sort the tokens of each text alphabetically.
+    binary
+        bool, default=False.
+        Whether to only record the presence of tokens, all counts are then 1.
 
     Examples
     --------
@@ -46,6 +53,9 @@ def count(
     >>> counter.count("Hello, world!", sort=True)
     {'hello': 1, 'world': 1}
 
+    >>> counter.count("Hello hello world", sort=True, binary=True)
+    {'hello': 1, 'world': 1}
+
     >>> counter.count("Hello, world!", ngram_range=(2, 2), sort=True, normalize=False)
     {'Hello, world!': 1}
 
@@ -72,16 +82,28 @@ def count(
     match analyzer:
         case "word":
             y = rsvectorize_split_words_many(
-                x, n_sizes=n_sizes, stop_words=stop_words, normalize=normalize
+                x,
+                n_sizes=n_sizes,
+                stop_words=stop_words,
+                normalize=normalize,
+                binary=binary,
             )
         case "char":
             y = rsvectorize_char_ngrams_many(
-                x, n_sizes=n_sizes, stop_words=stop_words, normalize=normalize
+                x,
+                n_sizes=n_sizes,
+                stop_words=stop_words,
+                normalize=normalize,
+                binary=binary,
             )
 
         case "char_wb":
             y = rsvectorize_char_wb_ngrams_many(
-                x, n_sizes=n_sizes, stop_words=stop_words, normalize=normalize
+                x,
+                n_sizes=n_sizes,
+                stop_words=stop_words,
+                normalize=normalize,
+                binary=binary,
             )
 
     if sort:
@@ -15,6 +15,12 @@ class FlashText:
         bool, default=True.
         Whether to normalize the text before extracting keywords. It will lowercase the text
         and remove punctuation.
+    word_boundaries
+        {ascii, unicode}, default=ascii.
+        Characters that make up words, keywords only match as whole words. With ascii,
+        words are made of [0-9a-zA-Z_]. With unicode, words are made of Unicode
+        alphanumeric characters and _, so that "café" or "Москва" match without
+        normalization.
 
     Examples
     --------
@@ -26,28 +32,127 @@ class FlashText:
     >>> flash_text.extract(["Hello, world!", "world", "hello"])
     [[('hello', 0, 5), ('world', 7, 12)], [('world', 0, 5)], [('hello', 0, 5)]]
 
+    >>> flash_text = flash_text.add("earth", clean_name="world")
+    >>> flash_text.replace("Hello, Earth!")
+    'Hello, world!'
+
+    >>> len(flash_text), "earth" in flash_text
+    (3, True)
+
+    >>> flash_text = flash_text.remove("earth")
+    >>> flash_text.get_all_keywords()
+    {'hello': 'hello', 'world': 'world'}
+
+    >>> flash_text = flash.FlashText(normalize=False, word_boundaries="unicode")
+    >>> flash_text = flash_text.add(["Москва", "e-mail"]).add_non_word_boundary("-")
+    >>> flash_text.extract("Москва, e-mail")
+    [('Москва', 0, 6), ('e-mail', 8, 14)]
+
+    >>> flash_text.extract("Москва, e-mail", offsets="byte")
+    [('Москва', 0, 12), ('e-mail', 14, 20)]
+
+    >>> flash_text = flash.FlashText().add(["new york", "york city", "new york city hall"])
+    >>> flash_text.extract("New York city", policy="longest")
+    [('new york', 0, 8)]
+
+    >>> flash_text.extract("New York city", policy="overlapping")
+    [('new york', 0, 8), ('york city', 4, 13)]
+
+    >>> flash_text = flash.FlashText().add(["machine learning", "python"])
+    >>> flash_text.extract("machin learning with pyton", max_cost=1, return_details=True)
+    [('machine learning', 0, 15, 1), ('python', 21, 26, 1)]
+
     """
 
     def __init__(
         self,
         lowercase: bool = True,
         normalize: bool = True,
+        word_boundaries: str = "ascii",
     ) -> None:
-        self.flash = RSKeywordProcessor(lowercase=lowercase, normalize=normalize)
+        self.flash = RSKeywordProcessor(
+            lowercase=lowercase,
+            normalize=normalize,
+            word_boundaries=word_boundaries,
+        )
 
     def add(
         self,
         x: str | list[str],
         clean_name: str | None = None,
-    ) -> None:
+    ) -> "FlashText":
         """Add a keyword to the FlashText object."""
         x = [x] if isinstance(x, str) else x
         self.flash.add_keywords_many(x, clean_name)
         return self
 
-    def extract(self, x: str | list[str]) -> list[str]:
-        """Extract keywords from a sentence."""
+    def add_non_word_boundary(self, x: str | list[str]) -> "FlashText":
+        """Treat characters such as '-' or '.' as part of keywords rather than as word
+        boundaries. With normalize, punctuation is removed before matching."""
+        for character in x:
+            self.flash.add_non_word_boundary(character)
+        return self
+
+    def set_non_word_boundaries(self, x: str | list[str]) -> "FlashText":
+        """Replace the characters added with add_non_word_boundary, the characters of
+        word_boundaries stay part of keywords."""
+        self.flash.set_non_word_boundaries(list(x))
+        return self
+
+    def remove(self, x: str | list[str]) -> "FlashText":
+        """Remove a keyword from the FlashText object."""
+        x = [x] if isinstance(x, str) else x
+        self.flash.remove_keywords_many(x)
+        return self
+
+    def get_keyword(self, x: str) -> str | None:
+        """Clean name of a keyword, None if the keyword was not added."""
+        return self.flash.get_keyword(x)
+
+    def get_all_keywords(self) -> dict[str, str]:
+        """Mapping of the normalized keywords to their clean name."""
+        return dict(sorted(self.flash.get_all_keywords().items()))
+
+    def __len__(self) -> int:
+        return len(self.flash)
+
+    def __contains__(self, x: str) -> bool:
+        return x in self.flash
+
+    def extract(
+        self,
+        x: str | list[str],
+        offsets: str = "char",
+        policy: str = "longest",
+        max_cost: int = 0,
+        return_details: bool = False,
+    ) -> list[tuple[str, int, int]]:
+        """Extract keywords and their start and end offsets from a sentence.
+
+        Offsets count characters, as Python string slicing, or UTF-8 bytes with
+        offsets="byte". Keywords may span several words, the policy decides which
+        keywords starting at the same word are kept: longest keeps the longest one,
+        first keeps the shortest one and overlapping keeps all of them.
+
+        With max_cost above 0, keywords also match text within max_cost character
+        insertions, deletions or substitutions, such as typos. With return_details,
+        each keyword also comes with its edit distance, 0 for exact matches.
+        """
+        is_string = isinstance(x, str)
+        x = [x] if isinstance(x, str) else x
+        y = self.flash.extract_keywords_many(
+            x,
+            offsets=offsets,
+            policy=policy,
+            max_cost=max_cost,
+            return_details=return_details,
+        )
+        return y[0] if is_string else y
+
+    def replace(self, x: str | list[str]) -> str | list[str]:
+        """Replace keywords with their clean name, the rest of the text is kept
+        unchanged."""
         is_string = isinstance(x, str)
         x = [x] if isinstance(x, str) else x
-        y = self.flash.extract_keywords_many(x)
+        y = self.flash.replace_keywords_many(x)
         return y[0] if is_string else y
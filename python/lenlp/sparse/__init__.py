@@ -1,5 +1,15 @@
 from .bm25_vectorizer import BM25Vectorizer
+from .bm25f_vectorizer import BM25FVectorizer
 from .count_vectorizer import CountVectorizer
+from .hashing_vectorizer import HashingVectorizer
+from .similarity import cosine_top_k
 from .tfidf_vectorizer import TfidfVectorizer
 
-__all__ = ["BM25Vectorizer", "CountVectorizer", "TfidfVectorizer"]
+__all__ = [
+    "BM25Vectorizer",
+    "BM25FVectorizer",
+    "CountVectorizer",
+    "HashingVectorizer",
+    "TfidfVectorizer",
+    "cosine_top_k",
+]
@@ -1,6 +1,7 @@
 import numpy as np
 from scipy.sparse import csr_matrix
-from sklearn.utils.sparsefuncs_fast import inplace_csr_row_normalize_l2
+
+from lenlp._rslenlp import InvertedIndex
 
 from .tfidf_vectorizer import TfidfVectorizer
 
@@ -27,6 +28,31 @@ class BM25Vectorizer(TfidfVectorizer):
     stop_words
         list of str, default=None.
         A list of stop words that will be removed from the text.
+    min_df
+        int or float, default=1.
+        Ignore terms with a document frequency strictly lower than this threshold. A
+        float is a proportion of documents, an int an absolute count.
+    max_df
+        int or float, default=1.0.
+        Ignore terms with a document frequency strictly higher than this threshold. A
+        float is a proportion of documents, an int an absolute count.
+    max_features
+        int, default=None.
+        Only keep the max_features most frequent terms across the corpus.
+    column_order
+        {first_occurrence, lexicographic, frequency}, default=first_occurrence.
+        How column indices are assigned to terms: by first document containing the
+        term, alphabetically or by decreasing corpus frequency. Ties are broken
+        alphabetically so that identical corpora always give identical vocabularies.
+    vocabulary
+        dict of str to int or list of str, default=None.
+        Predefined mapping from terms to column indices, or terms ordered by column.
+        When given, fit keeps the vocabulary unchanged and only computes document
+        statistics, min_df, max_df, max_features and column_order are ignored.
+    binary
+        bool, default=False.
+        Whether to only record the presence of terms. Count matrices are then stored as
+        uint8 and weighted matrices use a term frequency of 1.
     b
         The impact of document length normalization.  Default is `0.75`, Higher will
         penalize longer documents more.
@@ -35,6 +61,19 @@ class BM25Vectorizer(TfidfVectorizer):
         will make term frequency more influential.
     epsilon
         Smoothing term. Default is `0`.
+    variant
+        {okapi, bm25+, bm25l, bm25-adpt}, default=okapi.
+        Term frequency saturation. BM25+ adds a lower bound delta to the weight of every
+        matching term, BM25L shifts the length-normalized term frequency by delta, both
+        avoid over-penalizing long documents. BM25-adpt fits k1 and the idf of each term
+        on the fitted documents, k1 is used for terms that are never repeated.
+    delta
+        float, default=None.
+        Shift used by bm25+ and bm25l, defaults to 1.0 for bm25+ and 0.5 for bm25l.
+    idf_variant
+        {lucene, atire}, default=lucene.
+        Inverse document frequency, lucene is ln(1 + (n - df + 0.5) / (df + 0.5)) and
+        atire is ln(n / df).
 
     Examples
     --------
@@ -60,6 +99,18 @@ class BM25Vectorizer(TfidfVectorizer):
     >>> matrix.shape
     (2, 5)
 
+    >>> index = bm25_vectorizer.build_index(x)
+    >>> [[doc_id for doc_id, _ in results] for results in index.search(["hello"], k=10)]
+    [[0]]
+
+    >>> bm25_vectorizer = sparse.BM25Vectorizer(variant="bm25l", idf_variant="atire")
+    >>> bm25_vectorizer.fit_transform(x).shape
+    (2, 5)
+
+    >>> bm25_vectorizer = sparse.BM25Vectorizer(variant="bm25-adpt").fit(x)
+    >>> bm25_vectorizer.transform(x).shape
+    (2, 5)
+
     """
 
     def __init__(
@@ -68,53 +119,129 @@ class BM25Vectorizer(TfidfVectorizer):
         ngram_range: tuple[int, int] = (1, 1),
         normalize: bool = True,
         stop_words: list[str] = None,
+        min_df: int | float = 1,
+        max_df: int | float = 1.0,
+        max_features: int | None = None,
+        column_order: str = "first_occurrence",
+        vocabulary: dict[str, int] | list[str] | None = None,
+        binary: bool = False,
         k1: float = 1.5,
         b: float = 0.75,
         epsilon: float = 0,
+        variant: str = "okapi",
+        delta: float | None = None,
+        idf_variant: str = "lucene",
     ) -> None:
         super().__init__(
             analyzer=analyzer,
             ngram_range=ngram_range,
             normalize=normalize,
             stop_words=stop_words,
+            min_df=min_df,
+            max_df=max_df,
+            max_features=max_features,
+            column_order=column_order,
+            vocabulary=vocabulary,
+            binary=binary,
         )
 
         self.k1 = k1
         self.b = b
         self.epsilon = epsilon
-        self.average_len = None
-
-    def update(self, matrix: csr_matrix) -> csr_matrix:
-        """Update the idf values."""
-        self.tf = (matrix > 0).sum(axis=0)
-        len_documents = (matrix).sum(axis=1)
-        self.average_len = len_documents.mean()
-        self.count = matrix.shape[0]
-
-        self.idf = np.squeeze(
-            a=np.asarray(
-                a=np.log((self.count - self.tf + 0.5) / (self.tf + 0.5) + 1),
-                dtype=np.float32,
+        self.variant = variant
+        self.delta = delta
+        self.idf_variant = idf_variant
+
+    def __setstate__(self, state: dict) -> None:
+        """Restore a pickled vectorizer. The document statistics of pickles written by
+        LeNLP 1.2.0 are rebuilt from their document frequencies, number of documents and
+        average length, weighting options added since then take their default."""
+        if "variant" not in state:
+            state = dict(state)
+            state.pop("idf", None)
+            average_len = state.pop("average_len", None)
+            if "tf" in state:
+                n_documents = int(state.pop("count"))
+                state["sparse_matrix"].set_document_statistics(
+                    document_frequencies=np.asarray(state.pop("tf")).ravel().tolist(),
+                    n_documents=n_documents,
+                    n_tokens=round(float(average_len) * n_documents),
+                )
+            state.update(
+                norm="l2",
+                use_idf=True,
+                sublinear_tf=False,
+                variant="okapi",
+                delta=None,
+                idf_variant="lucene",
             )
+        super().__setstate__(state)
+
+    def fit(self, raw_documents: list[str]) -> None:
+        """Learn the vocabulary and the document frequencies, and the per-term k1 and
+        idf of bm25-adpt."""
+        self.fitted = True
+        if self.variant == "bm25-adpt":
+            self.sparse_matrix.fit_bm25_adpt(raw_documents, b=self.b)
+        else:
+            self.sparse_matrix.fit(raw_documents)
+        return self
+
+    def build_index(self, raw_documents: list[str]) -> InvertedIndex:
+        """Index the documents with the fitted vocabulary. The search method of the
+        index returns the top-k (document, score) pairs of each query, ranked as the
+        product of the document matrix and the transposed query matrix."""
+        if not self.fitted:
+            raise ValueError("Call fit method before calling build_index method.")
+
+        return InvertedIndex(
+            self.sparse_matrix,
+            raw_documents,
+            k1=self.k1,
+            b=self.b,
+            epsilon=self.epsilon,
+            variant=self.variant,
+            delta=self.delta,
+            idf=self.idf_variant,
         )
 
-    def _transform(self, matrix: csr_matrix) -> csr_matrix:
-        """Transform a count matrix to a bm25 matrix."""
-        len_documents = (matrix).sum(axis=1)
-        regularization = np.squeeze(
-            a=np.asarray(
-                a=(
-                    self.k1 * (1 - self.b + self.b * (len_documents / self.average_len))
-                ).flatten()
-            )
+    def transform(self, raw_documents: list[str]) -> csr_matrix:
+        """Transform documents to bm25 matrix."""
+        if not self.fitted:
+            raise ValueError("Call fit method before calling transform method.")
+
+        indptr, indices, data = self.sparse_matrix.transform_bm25(
+            raw_documents,
+            k1=self.k1,
+            b=self.b,
+            epsilon=self.epsilon,
+            variant=self.variant,
+            delta=self.delta,
+            idf=self.idf_variant,
         )
 
-        denominator = matrix.tocsc()
-        denominator.data += np.take(a=regularization, indices=denominator.indices)
-        matrix.data = (
-            (matrix.data * (self.k1 + 1)) / denominator.tocsr().data
-        ) + self.epsilon
+        return csr_matrix(
+            arg1=(data, indices, indptr),
+            shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
+            dtype=np.float32,
+        )
 
-        matrix = matrix.multiply(other=self.idf).tocsr()
-        inplace_csr_row_normalize_l2(matrix)
-        return matrix
+    def fit_transform(self, raw_documents: list[str]) -> csr_matrix:
+        """Learn the vocabulary and return the bm25 matrix."""
+        self.fitted = True
+
+        indptr, indices, data = self.sparse_matrix.fit_transform_bm25(
+            raw_documents,
+            k1=self.k1,
+            b=self.b,
+            epsilon=self.epsilon,
+            variant=self.variant,
+            delta=self.delta,
+            idf=self.idf_variant,
+        )
+
+        return csr_matrix(
+            arg1=(data, indices, indptr),
+            shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
+            dtype=np.float32,
+        )
@@ -0,0 +1,207 @@
+import os
+
+import numpy as np
+from scipy.sparse import csr_matrix
+
+from lenlp._rslenlp import MultiFieldMatrixBuilder
+
+__all__ = ["BM25FVectorizer"]
+
+FIELD_OPTIONS = ("analyzer", "ngram_range", "normalize", "stop_words", "boost", "b")
+
+
+class BM25FVectorizer:
+    """BM25FVectorizer is a class that converts a collection of documents made of several
+    text fields to a sparse BM25F matrix.
+
+    Parameters
+    ----------
+    fields
+        dict of str to dict.
+        Configuration of each field, keyed by field name. Each configuration accepts
+        analyzer (default=word), ngram_range (default=(1, 1)), normalize (default=True),
+        stop_words (default=None), boost (default=1.0), the weight of the field term
+        frequencies, and b (default=0.75), the impact of the field length normalization.
+        Unknown options raise a ValueError.
+    field_vocabulary
+        {shared, prefixed}, default=shared.
+        Whether a term found in several fields maps to a single column or each field
+        has its own columns, named field:term.
+    column_order
+        {first_occurrence, lexicographic, frequency}, default=first_occurrence.
+        How column indices are assigned to terms: by first document containing the
+        term, alphabetically or by decreasing corpus frequency.
+    k1
+        How quickly the impact of term frequency saturates.  Default is `1.2`, Higher
+        will make term frequency more influential.
+    idf_variant
+        {lucene, atire}, default=lucene.
+        Inverse document frequency, lucene is ln(1 + (n - df + 0.5) / (df + 0.5)) and
+        atire is ln(n / df).
+
+    Examples
+    --------
+    >>> from lenlp import sparse
+
+    >>> bm25f_vectorizer = sparse.BM25FVectorizer(
+    ...     fields={"title": {"boost": 3.0, "b": 0.5}, "body": {}},
+    ... )
+
+    >>> x = [
+    ...     {"title": "Hello", "body": "Hello, world!"},
+    ...     {"title": "Greetings", "body": "How are you?"},
+    ... ]
+
+    >>> matrix = bm25f_vectorizer.fit_transform(x)
+    >>> matrix.shape
+    (2, 6)
+
+    >>> bm25f_vectorizer.get_feature_names_out()
+    array(['hello', 'world', 'are', 'greetings', 'how', 'you'], dtype=object)
+
+    >>> bm25f_vectorizer = sparse.BM25FVectorizer(
+    ...     fields={"title": {"boost": 3.0}, "body": {}},
+    ...     field_vocabulary="prefixed",
+    ... )
+
+    >>> bm25f_vectorizer = bm25f_vectorizer.fit(x)
+    >>> bm25f_vectorizer.transform(x).shape
+    (2, 7)
+
+    Documents may omit fields, fields missing from the configuration raise a ValueError.
+
+    >>> bm25f_vectorizer.transform([{"tags": "hello"}])
+    Traceback (most recent call last):
+        ...
+    ValueError: Unknown field 'tags'.
+
+    >>> import tempfile, os
+    >>> with tempfile.TemporaryDirectory() as directory:
+    ...     path = os.path.join(directory, "bm25f.lenlp")
+    ...     bm25f_vectorizer.save(path)
+    ...     restored = sparse.BM25FVectorizer.load(path)
+    >>> restored.vocabulary == bm25f_vectorizer.vocabulary
+    True
+
+    """
+
+    def __init__(
+        self,
+        fields: dict[str, dict],
+        field_vocabulary: str = "shared",
+        column_order: str = "first_occurrence",
+        k1: float = 1.2,
+        idf_variant: str = "lucene",
+    ) -> None:
+        configs = []
+        for name, config in fields.items():
+            unknown = sorted(set(config) - set(FIELD_OPTIONS))
+            if unknown:
+                raise ValueError(
+                    f"Unknown option {unknown[0]!r} for field {name!r}, expected one of "
+                    f"{', '.join(FIELD_OPTIONS)}."
+                )
+            analyzer = config.get("analyzer", "word")
+            assert analyzer in ("word", "char", "char_wb")
+            ngram_range = config.get("ngram_range", (1, 1))
+            configs.append(
+                {
+                    "name": name,
+                    "analyzer": analyzer,
+                    "n_sizes": list(range(ngram_range[0], ngram_range[1] + 1)),
+                    "stop_words": config.get("stop_words"),
+                    "normalize": config.get("normalize", True),
+                    "boost": config.get("boost", 1.0),
+                    "b": config.get("b", 0.75),
+                }
+            )
+
+        self.sparse_matrix = MultiFieldMatrixBuilder(
+            fields=configs,
+            field_vocabulary=field_vocabulary,
+            column_order=column_order,
+        )
+
+        self.k1 = k1
+        self.idf_variant = idf_variant
+        self.fitted = False
+
+    @property
+    def vocabulary(self) -> dict[str, int]:
+        """Get the vocabulary of the BM25FVectorizer object."""
+        return self.sparse_matrix.get_vocab()
+
+    @property
+    def document_frequencies_(self) -> np.ndarray:
+        """Number of fitted documents containing each term in any field, aligned with
+        the columns."""
+        return self.sparse_matrix.get_document_frequencies()
+
+    @property
+    def average_field_lengths_(self) -> dict[str, float]:
+        """Average number of tokens of each field over the fitted documents."""
+        return self.sparse_matrix.get_average_field_lens()
+
+    def get_feature_names_out(self) -> np.ndarray:
+        """Terms ordered by column index."""
+        return np.asarray(self.sparse_matrix.get_feature_names_out(), dtype=object)
+
+    def save(self, path: str | os.PathLike) -> None:
+        """Write the field configurations, the vocabulary and the field statistics to a
+        versioned binary file."""
+        if not self.fitted:
+            raise ValueError("Call fit method before calling save method.")
+        self.sparse_matrix.save(path)
+
+    @classmethod
+    def load(
+        cls, path: str | os.PathLike, k1: float = 1.2, idf_variant: str = "lucene"
+    ) -> "BM25FVectorizer":
+        """Read a vectorizer written by save. The field configurations are read from the
+        file, k1 and idf_variant are not stored in the file."""
+        vectorizer = cls.__new__(cls)
+        vectorizer.sparse_matrix = MultiFieldMatrixBuilder.load(path)
+        vectorizer.k1 = k1
+        vectorizer.idf_variant = idf_variant
+        vectorizer.fitted = True
+        return vectorizer
+
+    def to_json(self) -> str:
+        """Human-readable JSON export of the field configurations, vocabulary and
+        statistics."""
+        return self.sparse_matrix.to_json()
+
+    def fit(self, raw_documents: list[dict[str, str]]) -> None:
+        """Learn the vocabulary, the document frequencies and the field lengths."""
+        self.fitted = True
+        self.sparse_matrix.fit(raw_documents)
+        return self
+
+    def transform(self, raw_documents: list[dict[str, str]]) -> csr_matrix:
+        """Transform documents to bm25f matrix."""
+        if not self.fitted:
+            raise ValueError("Call fit method before calling transform method.")
+
+        indptr, indices, data = self.sparse_matrix.transform_bm25f(
+            raw_documents, k1=self.k1, idf=self.idf_variant
+        )
+
+        return csr_matrix(
+            arg1=(data, indices, indptr),
+            shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
+            dtype=np.float32,
+        )
+
+    def fit_transform(self, raw_documents: list[dict[str, str]]) -> csr_matrix:
+        """Learn the vocabulary and return the bm25f matrix."""
+        self.fitted = True
+
+        indptr, indices, data = self.sparse_matrix.fit_transform_bm25f(
+            raw_documents, k1=self.k1, idf=self.idf_variant
+        )
+
+        return csr_matrix(
+            arg1=(data, indices, indptr),
+            shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
+            dtype=np.float32,
+        )
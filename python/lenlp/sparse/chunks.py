@@ -0,0 +1,39 @@
+import itertools
+import os
+from typing import Iterable, Iterator
+
+__all__ = ["iter_chunks"]
+
+
+def iter_chunks(
+    documents: Iterable[str] | str | os.PathLike, chunk_size: int
+) -> Iterator[list[str]]:
+    """Yield lists of at most chunk_size documents.
+
+    Parameters
+    ----------
+    documents
+        An iterable of documents, or the path of a text file with one document per line.
+    chunk_size
+        Number of documents per chunk.
+
+    Examples
+    --------
+    >>> from lenlp.sparse.chunks import iter_chunks
+
+    >>> list(iter_chunks(["a", "b", "c"], chunk_size=2))
+    [['a', 'b'], ['c']]
+
+    """
+    assert chunk_size > 0
+
+    if isinstance(documents, (str, os.PathLike)):
+        with open(documents, encoding="utf-8") as f:
+            yield from iter_chunks(
+                documents=(line.rstrip("\n") for line in f), chunk_size=chunk_size
+            )
+        return
+
+    documents = iter(documents)
+    while chunk := list(itertools.islice(documents, chunk_size)):
+        yield chunk
@@ -1,8 +1,13 @@
+import os
+from typing import Iterable, Iterator
+
 import numpy as np
 from scipy.sparse import csr_matrix
 
 from lenlp._rslenlp import SparseMatrixBuilder
 
+from .chunks import iter_chunks
+
 __all__ = ["CountVectorizer"]
 
 
@@ -28,6 +33,31 @@ class CountVectorizer:
     stop_words
         list of str, default=None.
         A list of stop words that will be removed from the text.
+    min_df
+        int or float, default=1.
+        Ignore terms with a document frequency strictly lower than this threshold. A
+        float is a proportion of documents, an int an absolute count.
+    max_df
+        int or float, default=1.0.
+        Ignore terms with a document frequency strictly higher than this threshold. A
+        float is a proportion of documents, an int an absolute count.
+    max_features
+        int, default=None.
+        Only keep the max_features most frequent terms across the corpus.
+    column_order
+        {first_occurrence, lexicographic, frequency}, default=first_occurrence.
+        How column indices are assigned to terms: by first document containing the
+        term, alphabetically or by decreasing corpus frequency. Ties are broken
+        alphabetically so that identical corpora always give identical vocabularies.
+    vocabulary
+        dict of str to int or list of str, default=None.
+        Predefined mapping from terms to column indices, or terms ordered by column.
+        When given, fit keeps the vocabulary unchanged and only computes document
+        statistics, min_df, max_df, max_features and column_order are ignored.
+    binary
+        bool, default=False.
+        Whether to only record the presence of terms. Count matrices are then stored as
+        uint8 and weighted matrices use a term frequency of 1.
 
     Examples
     --------
@@ -54,10 +84,33 @@ class CountVectorizer:
     >>> len(count_vectorizer.vocabulary)
     5
 
+    >>> count_vectorizer.get_feature_names_out()
+    array(['hello', 'world', 'are', 'how', 'you'], dtype=object)
+
+    >>> count_vectorizer.inverse_transform(matrix)
+    [['hello', 'world'], ['are', 'how', 'you']]
+
+    >>> count_vectorizer.document_lengths_
+    array([2, 3], dtype=uint64)
+
+    >>> count_vectorizer.average_document_length_
+    2.5
+
+    >>> count_vectorizer = count_vectorizer.fit_chunks(x, chunk_size=1)
+    >>> [matrix.shape for matrix in count_vectorizer.transform_chunks(x, chunk_size=1)]
+    [(1, 5), (1, 5)]
+
     >>> matrix = count_vectorizer.fit_transform(x)
     >>> matrix.shape
     (2, 5)
 
+    >>> import os, tempfile
+    >>> path = os.path.join(tempfile.mkdtemp(), "vectorizer.lenlp")
+    >>> sparse.CountVectorizer(binary=True).fit(x).save(path)
+    >>> loaded = sparse.CountVectorizer.load(path)
+    >>> loaded.binary, loaded.transform(x).dtype
+    (True, dtype('uint8'))
+
     """
 
     def __init__(
@@ -66,54 +119,172 @@ class CountVectorizer:
         ngram_range: tuple[int, int] = (1, 1),
         normalize: bool = True,
         stop_words: list[str] = None,
+        min_df: int | float = 1,
+        max_df: int | float = 1.0,
+        max_features: int | None = None,
+        column_order: str = "first_occurrence",
+        vocabulary: dict[str, int] | list[str] | None = None,
+        binary: bool = False,
     ) -> None:
         assert analyzer in ("word", "char", "char_wb")
 
         self.sparse_matrix = SparseMatrixBuilder(
-            analyzer=analyzer,
-            n_sizes=list(range(ngram_range[0], ngram_range[1] + 1)),
-            normalize=normalize,
-            stop_words=stop_words,
+            **self._builder_kwargs(
+                analyzer=analyzer,
+                n_sizes=list(range(ngram_range[0], ngram_range[1] + 1)),
+                normalize=normalize,
+                stop_words=stop_words,
+                min_df=min_df,
+                max_df=max_df,
+                max_features=max_features,
+                column_order=column_order,
+                vocabulary=vocabulary,
+                binary=binary,
+            )
         )
 
-        self.fitted = False
+        self.binary = binary
+        self.fitted = vocabulary is not None
+
+    def _builder_kwargs(self, **kwargs) -> dict:
+        """Keyword arguments of the SparseMatrixBuilder, subclasses extend them with
+        their weighting options."""
+        return kwargs
 
     @property
     def vocabulary(self) -> dict[str, int]:
         """Get the vocabulary of the CountVectorizer object."""
         return self.sparse_matrix.get_vocab()
 
+    @property
+    def fixed_vocabulary_(self) -> bool:
+        """Whether the vocabulary was given to the constructor."""
+        return self.sparse_matrix.get_fixed_vocabulary()
+
+    @property
+    def stop_words_(self) -> list[str]:
+        """Terms ignored because of min_df, max_df or max_features."""
+        return self.sparse_matrix.get_pruned_terms()
+
+    @property
+    def document_frequencies_(self) -> np.ndarray:
+        """Number of fitted documents containing each term, aligned with the columns."""
+        return self.sparse_matrix.get_document_frequencies()
+
+    @property
+    def collection_frequencies_(self) -> np.ndarray:
+        """Number of occurrences of each term in the fitted corpus, aligned with the
+        columns."""
+        return self.sparse_matrix.get_collection_frequencies()
+
+    @property
+    def document_lengths_(self) -> np.ndarray:
+        """Number of in-vocabulary tokens of each fitted document. After fit_chunks,
+        tokens of pruned terms are counted as well."""
+        return self.sparse_matrix.get_document_lengths()
+
+    @property
+    def average_document_length_(self) -> float:
+        """Average number of in-vocabulary tokens per fitted document."""
+        return self.sparse_matrix.get_average_len()
+
+    def get_feature_names_out(self) -> np.ndarray:
+        """Terms ordered by column index."""
+        return np.asarray(self.sparse_matrix.get_feature_names_out(), dtype=object)
+
+    def inverse_transform(self, X: csr_matrix) -> list[list[str]]:
+        """Return the terms of the non-zero columns of each row of X."""
+        if not self.fitted:
+            raise ValueError("Call fit method before calling inverse_transform method.")
+
+        X = csr_matrix(X)
+        X.eliminate_zeros()
+        return self.sparse_matrix.inverse_transform(
+            np.asarray(X.indptr, dtype=np.int64),
+            np.asarray(X.indices, dtype=np.int64),
+        )
+
+    def save(self, path: str | os.PathLike) -> None:
+        """Write the analyzer configuration, the vocabulary and the document statistics
+        to a versioned binary file."""
+        if not self.fitted:
+            raise ValueError("Call fit method before calling save method.")
+        self.sparse_matrix.save(path)
+
+    @classmethod
+    def load(cls, path: str | os.PathLike, **kwargs) -> "CountVectorizer":
+        """Read a vectorizer written by save. The analyzer configuration and binary are
+        read from the file, weighting parameters such as norm or k1 are not stored in the
+        file and are given as keyword arguments."""
+        vectorizer = cls(**kwargs)
+        vectorizer.sparse_matrix = SparseMatrixBuilder.load(path)
+        vectorizer.binary = vectorizer.sparse_matrix.get_binary()
+        vectorizer.fitted = True
+        return vectorizer
+
+    def __setstate__(self, state: dict) -> None:
+        """Restore a pickled vectorizer, pickles written by LeNLP 1.2.0 predate binary
+        counting."""
+        state.setdefault("binary", False)
+        self.__dict__.update(state)
+
+    def to_json(self) -> str:
+        """Human-readable JSON export of the configuration, vocabulary and statistics."""
+        return self.sparse_matrix.to_json()
+
     def fit(self, raw_documents: list[str]) -> None:
         """Learn the vocabulary dictionary and return the CountVectorizer object."""
         self.fitted = True
         self.sparse_matrix.fit(raw_documents)
         return self
 
+    def partial_fit(self, raw_documents: list[str]) -> None:
+        """Update the vocabulary with new documents, existing columns are preserved."""
+        self.fitted = True
+        self.sparse_matrix.partial_fit(raw_documents)
+        return self
+
+    def fit_chunks(
+        self,
+        raw_documents: Iterable[str] | str | os.PathLike,
+        chunk_size: int = 10_000,
+    ) -> None:
+        """Learn the vocabulary from an iterable or a file with one document per line,
+        reading chunk_size documents at a time."""
+        self.fitted = True
+        self.sparse_matrix.fit_chunks(iter_chunks(raw_documents, chunk_size=chunk_size))
+        return self
+
+    def transform_chunks(
+        self,
+        raw_documents: Iterable[str] | str | os.PathLike,
+        chunk_size: int = 10_000,
+    ) -> Iterator[csr_matrix]:
+        """Yield one document-term matrix per chunk of chunk_size documents."""
+        for chunk in iter_chunks(raw_documents, chunk_size=chunk_size):
+            yield self.transform(chunk)
+
     def transform(self, raw_documents: list[str]) -> csr_matrix:
         """Transform documents to document-term matrix."""
         if not self.fitted:
             raise ValueError("Call fit method before calling transform method.")
 
-        values, row_indices, column_indices = self.sparse_matrix.transform(
-            raw_documents
-        )
+        indptr, indices, data = self.sparse_matrix.transform(raw_documents)
 
         return csr_matrix(
-            arg1=(values, (row_indices, column_indices)),
+            arg1=(data, indices, indptr),
             shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
-            dtype=np.float32,
+            dtype=np.uint8 if self.binary else np.float32,
         )
 
     def fit_transform(self, raw_documents: list[str]) -> csr_matrix:
         """Learn the vocabulary dictionary and return the CountVectorizer object."""
         self.fitted = True
 
-        values, row_indices, column_indices = self.sparse_matrix.fit_transform(
-            raw_documents
-        )
+        indptr, indices, data = self.sparse_matrix.fit_transform(raw_documents)
 
         return csr_matrix(
-            arg1=(values, (row_indices, column_indices)),
+            arg1=(data, indices, indptr),
             shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
-            dtype=np.float32,
+            dtype=np.uint8 if self.binary else np.float32,
         )
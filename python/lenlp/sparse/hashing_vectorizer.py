@@ -0,0 +1,112 @@
+import numpy as np
+from scipy.sparse import csr_matrix
+
+from lenlp._rslenlp import rshash_vectorize_many
+
+__all__ = ["HashingVectorizer"]
+
+
+class HashingVectorizer:
+    """HashingVectorizer is a class that converts a collection of text documents to a sparse
+    matrix of hashed token counts. It does not store any vocabulary.
+
+    Parameters
+    ----------
+    analyzer
+        {word, char, char_wb}, default=word.
+        Whether the feature should be made of word n-gram or character n-grams. Option
+        char_wb creates character n-grams only from text inside word boundaries;
+        n-grams at the edges of words are padded with space.
+    ngram_range
+        tuple (min_n, max_n), default=(1, 1).
+        The lower and upper boundary of the range of n-values for different n-grams to
+        be extracted. All values of n such that min_n <= n <= max_n will be used.
+    normalize
+        bool, default=True.
+        Whether to normalize the text before counting. It will lowercase the text and remove
+        punctuation.
+    stop_words
+        list of str, default=None.
+        A list of stop words that will be removed from the text.
+    n_features
+        int, default=2**20.
+        The number of columns of the output matrix. Tokens are assigned to columns with
+        the MurmurHash3 function, as in scikit-learn.
+    alternate_sign
+        bool, default=True.
+        Whether to flip the sign of the tokens with a negative hash, so that collisions
+        tend to cancel out.
+    norm
+        {l1, l2, None}, default=l2.
+        Norm used to normalize each row of the matrix.
+
+    Examples
+    --------
+    >>> from lenlp import sparse
+
+    >>> hashing_vectorizer = sparse.HashingVectorizer(
+    ...     analyzer="word",
+    ...     normalize=True,
+    ...     stop_words=None,
+    ...     n_features=16,
+    ... )
+
+    >>> x = ["Hello, world!", "How are you?"]
+
+    >>> matrix = hashing_vectorizer.transform(x)
+    >>> matrix.shape
+    (2, 16)
+
+    >>> matrix = hashing_vectorizer.fit_transform(x)
+    >>> matrix.shape
+    (2, 16)
+
+    """
+
+    def __init__(
+        self,
+        analyzer: str = "word",
+        ngram_range: tuple[int, int] = (1, 1),
+        normalize: bool = True,
+        stop_words: list[str] = None,
+        n_features: int = 2**20,
+        alternate_sign: bool = True,
+        norm: str | None = "l2",
+    ) -> None:
+        assert analyzer in ("word", "char", "char_wb")
+        assert norm in ("l1", "l2", None)
+
+        self.analyzer = analyzer
+        self.n_sizes = list(range(ngram_range[0], ngram_range[1] + 1))
+        self.normalize = normalize
+        self.stop_words = stop_words
+        self.n_features = n_features
+        self.alternate_sign = alternate_sign
+        self.norm = norm
+
+    def fit(self, raw_documents: list[str]) -> None:
+        """HashingVectorizer is stateless, fitting does nothing."""
+        return self
+
+    def transform(self, raw_documents: list[str]) -> csr_matrix:
+        """Transform documents to hashed document-term matrix."""
+        indptr, indices, data = rshash_vectorize_many(
+            raw_documents,
+            self.n_sizes,
+            self.analyzer,
+            stop_words=self.stop_words,
+            normalize=self.normalize,
+            n_features=self.n_features,
+            alternate_sign=self.alternate_sign,
+            norm=self.norm,
+        )
+
+        return csr_matrix(
+            arg1=(data, indices, indptr),
+            shape=(len(raw_documents), self.n_features),
+            dtype=np.float32,
+        )
+
+    def fit_transform(self, raw_documents: list[str]) -> csr_matrix:
+        """Transform documents to hashed document-term matrix."""
+        return self.transform(raw_documents=raw_documents)
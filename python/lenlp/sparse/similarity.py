@@ -0,0 +1,73 @@
+import numpy as np
+from scipy.sparse import csr_matrix
+
+from lenlp._rslenlp import rscosine_top_k
+
+__all__ = ["cosine_top_k"]
+
+
+def cosine_top_k(
+    A: csr_matrix, B: csr_matrix, k: int = 10, threshold: float = 0.0
+) -> csr_matrix:
+    """Top-k cosine similarities between the rows of A and the rows of B, computed in
+    parallel without materializing A @ B.T.
+
+    Parameters
+    ----------
+    A
+        csr_matrix of shape (n_a, n_features).
+    B
+        csr_matrix of shape (n_b, n_features), sharing the columns of A.
+    k
+        int, default=10.
+        Number of rows of B kept for each row of A.
+    threshold
+        float, default=0.0.
+        Only similarities strictly greater than threshold are kept.
+
+    Returns
+    -------
+    csr_matrix of shape (n_a, n_b) holding the kept similarities.
+
+    Examples
+    --------
+    >>> from lenlp import sparse
+
+    >>> tfidf_vectorizer = sparse.TfidfVectorizer(analyzer="char_wb", ngram_range=(3, 3))
+    >>> A = tfidf_vectorizer.fit_transform(["Apple Inc", "Microsoft Corp"])
+    >>> B = tfidf_vectorizer.transform(["apple", "microsoft", "alphabet"])
+
+    >>> similarities = sparse.cosine_top_k(A, B, k=1, threshold=0.1)
+    >>> similarities.shape
+    (2, 3)
+
+    >>> similarities.indices.tolist()
+    [0, 1]
+
+    """
+    # Copies keep the caller's matrices untouched: sum_duplicates works in place,
+    # merging repeated entries and sorting indices so the row norms are exact.
+    A, B = csr_matrix(A, copy=True), csr_matrix(B, copy=True)
+    A.sum_duplicates()
+    B.sum_duplicates()
+    if A.shape[1] != B.shape[1]:
+        raise ValueError(
+            f"A and B must have the same number of columns, got {A.shape[1]} and {B.shape[1]}."
+        )
+
+    def arrays(X: csr_matrix) -> tuple[np.ndarray, np.ndarray, np.ndarray]:
+        return (
+            np.asarray(X.indptr, dtype=np.int64),
+            np.asarray(X.indices, dtype=np.int64),
+            np.asarray(X.data, dtype=np.float32),
+        )
+
+    indptr, indices, data = rscosine_top_k(
+        arrays(A), arrays(B), k=k, threshold=threshold
+    )
+
+    return csr_matrix(
+        arg1=(data, indices, indptr),
+        shape=(A.shape[0], B.shape[0]),
+        dtype=np.float32,
+    )
@@ -1,6 +1,5 @@
 import numpy as np
 from scipy.sparse import csr_matrix
-from sklearn.utils.sparsefuncs_fast import inplace_csr_row_normalize_l2
 
 from .count_vectorizer import CountVectorizer
 
@@ -27,6 +26,44 @@ class TfidfVectorizer(CountVectorizer):
     stop_words
         list of str, default=None.
         A list of stop words that will be removed from the text.
+    min_df
+        int or float, default=1.
+        Ignore terms with a document frequency strictly lower than this threshold. A
+        float is a proportion of documents, an int an absolute count.
+    max_df
+        int or float, default=1.0.
+        Ignore terms with a document frequency strictly higher than this threshold. A
+        float is a proportion of documents, an int an absolute count.
+    max_features
+        int, default=None.
+        Only keep the max_features most frequent terms across the corpus.
+    column_order
+        {first_occurrence, lexicographic, frequency}, default=first_occurrence.
+        How column indices are assigned to terms: by first document containing the
+        term, alphabetically or by decreasing corpus frequency. Ties are broken
+        alphabetically so that identical corpora always give identical vocabularies.
+    vocabulary
+        dict of str to int or list of str, default=None.
+        Predefined mapping from terms to column indices, or terms ordered by column.
+        When given, fit keeps the vocabulary unchanged and only computes document
+        statistics, min_df, max_df, max_features and column_order are ignored.
+    binary
+        bool, default=False.
+        Whether to only record the presence of terms. Count matrices are then stored as
+        uint8 and weighted matrices use a term frequency of 1.
+    norm
+        {l1, l2, None}, default=l2.
+        Norm used to normalize each row of the tfidf matrix.
+    use_idf
+        bool, default=True.
+        Whether to reweight the term frequencies with the inverse document frequency.
+    smooth_idf
+        bool, default=True.
+        Whether to add one to document frequencies, as if an extra document contained
+        every term once. Prevents zero divisions.
+    sublinear_tf
+        bool, default=False.
+        Whether to replace term frequencies with 1 + log(tf).
 
     Examples
     --------
@@ -60,65 +97,97 @@ class TfidfVectorizer(CountVectorizer):
         ngram_range: tuple[int, int] = (1, 1),
         normalize: bool = True,
         stop_words: list[str] = None,
+        min_df: int | float = 1,
+        max_df: int | float = 1.0,
+        max_features: int | None = None,
+        column_order: str = "first_occurrence",
+        vocabulary: dict[str, int] | list[str] | None = None,
+        binary: bool = False,
+        norm: str | None = "l2",
+        use_idf: bool = True,
+        smooth_idf: bool = True,
+        sublinear_tf: bool = False,
     ) -> None:
+        self.smooth_idf = smooth_idf
+
         super().__init__(
             analyzer=analyzer,
             ngram_range=ngram_range,
             normalize=normalize,
             stop_words=stop_words,
+            min_df=min_df,
+            max_df=max_df,
+            max_features=max_features,
+            column_order=column_order,
+            vocabulary=vocabulary,
+            binary=binary,
         )
 
-        self.idf = None
+        assert norm in ("l1", "l2", None)
+
+        self.norm = norm
+        self.use_idf = use_idf
+        self.sublinear_tf = sublinear_tf
+
+    def _builder_kwargs(self, **kwargs) -> dict:
+        return {**super()._builder_kwargs(**kwargs), "smooth_idf": self.smooth_idf}
+
+    @property
+    def idf(self) -> np.ndarray:
+        """Get the idf vector of the TfidfVectorizer object."""
+        return self.sparse_matrix.get_idf()
+
+    def __setstate__(self, state: dict) -> None:
+        """Restore a pickled vectorizer. Pickles written by LeNLP 1.2.0 only store the
+        idf vector, which is kept until the next fit, weighting options added since then
+        take their default."""
+        if "norm" not in state:
+            state = dict(state)
+            idf = state.pop("idf", None)
+            if idf is not None:
+                state["sparse_matrix"].set_legacy_idf(
+                    np.asarray(idf, dtype=np.float32).ravel().tolist()
+                )
+            state.update(norm="l2", use_idf=True, sublinear_tf=False)
+        super().__setstate__(state)
 
     def fit(self, raw_documents: list[str]) -> None:
-        matrix = super().fit_transform(raw_documents=raw_documents)
-        self.update(matrix=matrix)
+        """Learn the vocabulary and the idf vector."""
+        self.fitted = True
+        self.sparse_matrix.fit(raw_documents)
         return self
 
-    def update(self, matrix: csr_matrix) -> csr_matrix:
-        """Update the idf values."""
-        tf = (matrix > 0).sum(axis=0)
-        self.idf = (
-            np.squeeze(a=np.asarray(a=np.log((matrix.shape[0] + 1.0) / (tf + 1.0)))) + 1
-        )
-
-    def _transform(self, matrix: csr_matrix) -> csr_matrix:
-        """Transform a count matrix to a bm25 matrix."""
-        matrix.data *= np.take(
-            a=self.idf,
-            indices=matrix.indices,
-        )
-
-        inplace_csr_row_normalize_l2(matrix)
-        return matrix
-
     def transform(self, raw_documents: list[str]) -> csr_matrix:
-        """Transform documents to document-term matrix."""
-        values, row_indices, column_indices = self.sparse_matrix.transform(
-            raw_documents
+        """Transform documents to tfidf matrix."""
+        if not self.fitted:
+            raise ValueError("Call fit method before calling transform method.")
+
+        indptr, indices, data = self.sparse_matrix.transform_tfidf(
+            raw_documents,
+            sublinear_tf=self.sublinear_tf,
+            use_idf=self.use_idf,
+            norm=self.norm,
         )
-        return self._transform(
-            matrix=csr_matrix(
-                arg1=(values, (row_indices, column_indices)),
-                shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
-                dtype=np.float32,
-            )
+
+        return csr_matrix(
+            arg1=(data, indices, indptr),
+            shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
+            dtype=np.float32,
         )
 
     def fit_transform(self, raw_documents: list[str]) -> csr_matrix:
-        """Learn the vocabulary dictionary and return the CountVectorizer object."""
-        values, row_indices, column_indices = self.sparse_matrix.fit_transform(
-            raw_documents
+        """Learn the vocabulary and return the tfidf matrix."""
+        self.fitted = True
+
+        indptr, indices, data = self.sparse_matrix.fit_transform_tfidf(
+            raw_documents,
+            sublinear_tf=self.sublinear_tf,
+            use_idf=self.use_idf,
+            norm=self.norm,
         )
 
-        matrix = csr_matrix(
-            arg1=(values, (row_indices, column_indices)),
+        return csr_matrix(
+            arg1=(data, indices, indptr),
             shape=(len(raw_documents), self.sparse_matrix.get_num_cols()),
             dtype=np.float32,
         )
-
-        self.update(matrix=matrix)
-
-        return self._transform(
-            matrix=matrix,
-        )
@@ -1,20 +1,49 @@
+//! Natural language processing toolbox written in Rust.
+//!
+//! The `python` feature, enabled by default, builds the `_rslenlp` extension module.
+//! Disable default features to use the analyzers, vectorizers and keyword processor
+//! as a plain Rust library.
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyModule; // NEW
 
-mod rsanalyzer;
-mod rscounter;
-mod rsflashtext;
-mod rsnormalizer;
-mod rssparse;
-mod rsstop_words;
-mod rsvectorizer;
+pub mod rsanalyzer;
+pub mod rscounter;
+pub mod rserror;
+pub mod rsflashtext;
+pub mod rshashing;
+pub mod rsindex;
+pub mod rsmultifield;
+pub mod rsnormalizer;
+pub mod rsserialization;
+pub mod rssimilarity;
+pub mod rssparse;
+pub mod rsstop_words;
+pub mod rsvectorizer;
 
+pub use rsanalyzer::Analyzer;
+pub use rserror::LeNLPError;
+pub use rsflashtext::{KeywordMatch, MatchPolicy, Offsets, RSKeywordProcessor, WordBoundaries};
+pub use rsindex::InvertedIndex;
+pub use rsmultifield::{FieldConfig, FieldVocabulary, MultiFieldMatrixBuilder};
+pub use rssimilarity::cosine_top_k;
+pub use rssparse::{
+    Bm25AdptTerms, Bm25Idf, Bm25Params, Bm25Variant, ColumnOrder, CsrMatrix, DocumentFrequency,
+    Norm, SparseMatrixBuilder, SparseMatrixBuilderOptions, Vocabulary,
+};
+
+#[cfg(feature = "python")]
 #[pymodule]
 fn _rslenlp(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     rsanalyzer::register_functions(m)?;
     rscounter::register_functions(m)?;
     rsflashtext::register_functions(m)?;
+    rshashing::register_functions(m)?;
+    rsindex::register_functions(m)?;
+    rsmultifield::register_functions(m)?;
     rsnormalizer::register_functions(m)?;
+    rssimilarity::register_functions(m)?;
     rssparse::register_functions(m)?;
     rsstop_words::register_functions(m)?;
     rsvectorizer::register_functions(m)?;
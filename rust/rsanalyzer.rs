@@ -1,23 +1,66 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::types::PyModule; // <- NEW: we now use the smart-pointer Bound<…, PyModule>
+#[cfg(feature = "python")]
 use pyo3::wrap_pyfunction;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
-/// Splits text into words.
-///
-/// # Arguments
-///
-/// * `text` - The input text.
-/// * `n_sizes` - The size of the n-grams.
-///
-/// # Returns
-///
-/// A vector of words.
-#[pyfunction]
-pub fn rssplit_words(text: &str, n_sizes: Vec<usize>) -> Vec<String> {
+use crate::rserror::LeNLPError;
+
+/// Kind of n-grams extracted from the texts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Analyzer {
+    Word,
+    Char,
+    CharWb,
+}
+
+impl Analyzer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Analyzer::Word => "word",
+            Analyzer::Char => "char",
+            Analyzer::CharWb => "char_wb",
+        }
+    }
+}
+
+impl FromStr for Analyzer {
+    type Err = LeNLPError;
+
+    fn from_str(analyzer: &str) -> Result<Self, Self::Err> {
+        match analyzer {
+            "word" => Ok(Analyzer::Word),
+            "char" => Ok(Analyzer::Char),
+            "char_wb" => Ok(Analyzer::CharWb),
+            _ => Err(LeNLPError::InvalidAnalyzer(analyzer.to_string())),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Analyzer {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<Analyzer>()?)
+    }
+}
+
+/// Checks that every n-gram size is strictly positive.
+pub fn validate_n_sizes(n_sizes: &[usize]) -> Result<(), LeNLPError> {
+    if n_sizes.contains(&0) {
+        return Err(LeNLPError::InvalidNgramSize);
+    }
+    Ok(())
+}
+
+pub(crate) fn split_words(text: &str, n_sizes: &[usize]) -> Vec<String> {
     let mut ngrams: Vec<String> = Vec::new();
 
-    for &n in &n_sizes {
+    for &n in n_sizes {
         let words: Vec<&str> = text.split_whitespace().collect();
         for window in words.windows(n) {
             ngrams.push(window.join(" "));
@@ -27,30 +70,10 @@ pub fn rssplit_words(text: &str, n_sizes: Vec<usize>) -> Vec<String> {
     ngrams
 }
 
-/// Same as `rssplit_words` but for many texts at once.
-#[pyfunction]
-pub fn rssplit_words_many(texts: Vec<String>, n_sizes: Vec<usize>) -> Vec<Vec<String>> {
-    texts
-        .par_iter()
-        .map(|text: &String| rssplit_words(text, n_sizes.clone()))
-        .collect()
-}
-
-/// Computes character n-grams.
-///
-/// # Arguments
-///
-/// * `texts` - A vector of input texts.
-/// * `n_sizes` - The size of the n-grams.
-///
-/// # Returns
-///
-/// A vector of character n-grams.
-#[pyfunction]
-pub fn rschar_ngrams(text: &str, n_sizes: Vec<usize>) -> Vec<String> {
+pub(crate) fn char_ngrams(text: &str, n_sizes: &[usize]) -> Vec<String> {
     let mut ngrams: Vec<String> = Vec::new();
 
-    for &n in &n_sizes {
+    for &n in n_sizes {
         let chars: Vec<char> = text.chars().collect();
         for window in chars.windows(n) {
             ngrams.push(window.iter().collect::<String>());
@@ -60,22 +83,11 @@ pub fn rschar_ngrams(text: &str, n_sizes: Vec<usize>) -> Vec<String> {
     ngrams
 }
 
-/// Same as `rschar_ngrams` but for many texts at once.
-#[pyfunction]
-pub fn rschar_ngrams_many(texts: Vec<String>, n_sizes: Vec<usize>) -> Vec<Vec<String>> {
-    texts
-        .par_iter()
-        .map(|text: &String| rschar_ngrams(text, n_sizes.clone()))
-        .collect()
-}
-
-/// Character n-grams with word-boundary handling.
-#[pyfunction]
-pub fn rschar_wb_ngrams(text: &str, n_sizes: Vec<usize>) -> Vec<String> {
+pub(crate) fn char_wb_ngrams(text: &str, n_sizes: &[usize]) -> Vec<String> {
     let mut ngrams: Vec<String> = Vec::new();
     let chars: Vec<char> = text.chars().collect();
 
-    for &n in &n_sizes {
+    for &n in n_sizes {
         if n > chars.len() {
             continue;
         }
@@ -87,18 +99,88 @@ pub fn rschar_wb_ngrams(text: &str, n_sizes: Vec<usize>) -> Vec<String> {
     ngrams
 }
 
+/// Splits text into words.
+///
+/// # Arguments
+///
+/// * `text` - The input text.
+/// * `n_sizes` - The size of the n-grams.
+///
+/// # Returns
+///
+/// A vector of words.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn rssplit_words(text: &str, n_sizes: Vec<usize>) -> Result<Vec<String>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
+    Ok(split_words(text, &n_sizes))
+}
+
+/// Same as `rssplit_words` but for many texts at once.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn rssplit_words_many(
+    texts: Vec<String>,
+    n_sizes: Vec<usize>,
+) -> Result<Vec<Vec<String>>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
+    Ok(texts
+        .par_iter()
+        .map(|text: &String| split_words(text, &n_sizes))
+        .collect())
+}
+
+/// Computes character n-grams.
+///
+/// # Arguments
+///
+/// * `texts` - A vector of input texts.
+/// * `n_sizes` - The size of the n-grams.
+///
+/// # Returns
+///
+/// A vector of character n-grams.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn rschar_ngrams(text: &str, n_sizes: Vec<usize>) -> Result<Vec<String>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
+    Ok(char_ngrams(text, &n_sizes))
+}
+
+/// Same as `rschar_ngrams` but for many texts at once.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn rschar_ngrams_many(
+    texts: Vec<String>,
+    n_sizes: Vec<usize>,
+) -> Result<Vec<Vec<String>>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
+    Ok(texts
+        .par_iter()
+        .map(|text: &String| char_ngrams(text, &n_sizes))
+        .collect())
+}
+
+/// Character n-grams with word-boundary handling.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn rschar_wb_ngrams(text: &str, n_sizes: Vec<usize>) -> Result<Vec<String>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
+    Ok(char_wb_ngrams(text, &n_sizes))
+}
+
 /// Same as `rschar_wb_ngrams` but for many texts at once.
-#[pyfunction]
-pub fn rschar_wb_ngrams_many(texts: Vec<String>, n_sizes: Vec<usize>) -> Vec<Vec<String>> {
-    texts
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn rschar_wb_ngrams_many(
+    texts: Vec<String>,
+    n_sizes: Vec<usize>,
+) -> Result<Vec<Vec<String>>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
+    Ok(texts
         .par_iter()
-        .map(|text: &String| rschar_wb_ngrams(text, n_sizes.clone()))
-        .collect()
+        .map(|text: &String| char_wb_ngrams(text, &n_sizes))
+        .collect())
 }
 
 /// Registers all the above functions in a Python sub-module.
 ///
 /// Called from your `#[pymodule]` entry-point.
+#[cfg(feature = "python")]
 pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(rssplit_words, m)?)?;
     m.add_function(wrap_pyfunction!(rssplit_words_many, m)?)?;
@@ -108,3 +190,33 @@ pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(rschar_wb_ngrams_many, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyzer_from_str() {
+        assert_eq!("char_wb".parse::<Analyzer>(), Ok(Analyzer::CharWb));
+        assert_eq!(
+            "chars".parse::<Analyzer>(),
+            Err(LeNLPError::InvalidAnalyzer("chars".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_n_sizes() {
+        assert_eq!(
+            rssplit_words("hello world", vec![1, 0]),
+            Err(LeNLPError::InvalidNgramSize)
+        );
+        assert_eq!(
+            rschar_ngrams_many(vec!["hello".to_string()], vec![0]),
+            Err(LeNLPError::InvalidNgramSize)
+        );
+        assert_eq!(
+            rssplit_words("hello world", vec![2]),
+            Ok(vec!["hello world".to_string()])
+        );
+    }
+}
@@ -1,4 +1,6 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::wrap_pyfunction;
 use rayon::prelude::*;
 use std::collections::HashMap;
@@ -12,7 +14,7 @@ use std::collections::HashMap;
 /// # Returns
 ///
 /// A hashmap with the words as keys and the number of times they appear as values.
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn rscount(text: Vec<String>) -> HashMap<String, usize> {
     let mut word_counter = HashMap::new();
     for word in text {
@@ -30,7 +32,7 @@ pub fn rscount(text: Vec<String>) -> HashMap<String, usize> {
 /// # Returns
 ///
 /// A vector of hashmaps with the words as keys and the number of times they appear as values.
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn rscount_many(texts: Vec<Vec<String>>) -> Vec<HashMap<String, usize>> {
     texts.par_iter().map(|text| rscount(text.clone())).collect()
 }
@@ -38,6 +40,7 @@ pub fn rscount_many(texts: Vec<Vec<String>>) -> Vec<HashMap<String, usize>> {
 /// Registers all the above functions in a Python sub-module.
 ///
 /// Called from your `#[pymodule]` entry-point.
+#[cfg(feature = "python")]
 pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(rscount, m)?)?;
     m.add_function(wrap_pyfunction!(rscount_many, m)?)?;
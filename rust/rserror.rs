@@ -0,0 +1,62 @@
+#[cfg(feature = "python")]
+use pyo3::exceptions::{PyIOError, PyValueError};
+#[cfg(feature = "python")]
+use pyo3::PyErr;
+use std::fmt;
+
+/// Errors raised by LeNLP, mapped to Python exceptions at the bindings boundary.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeNLPError {
+    /// The analyzer is not one of word, char or char_wb.
+    InvalidAnalyzer(String),
+    /// An n-gram size is zero.
+    InvalidNgramSize,
+    /// A parameter is outside of its accepted values.
+    InvalidParameter(String),
+    /// A serialized state could not be written or read.
+    Serialization(String),
+    /// A model file could not be written or read.
+    Io(String),
+}
+
+impl fmt::Display for LeNLPError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeNLPError::InvalidAnalyzer(analyzer) => write!(
+                f,
+                "Invalid analyzer '{}', expected 'word', 'char' or 'char_wb'.",
+                analyzer
+            ),
+            LeNLPError::InvalidNgramSize => write!(f, "n-gram sizes must be strictly positive."),
+            LeNLPError::InvalidParameter(message) => write!(f, "{}", message),
+            LeNLPError::Serialization(message) => {
+                write!(f, "Invalid serialized state: {}", message)
+            }
+            LeNLPError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for LeNLPError {}
+
+impl From<std::io::Error> for LeNLPError {
+    fn from(error: std::io::Error) -> Self {
+        LeNLPError::Io(error.to_string())
+    }
+}
+
+impl From<bincode::Error> for LeNLPError {
+    fn from(error: bincode::Error) -> Self {
+        LeNLPError::Serialization(error.to_string())
+    }
+}
+
+#[cfg(feature = "python")]
+impl From<LeNLPError> for PyErr {
+    fn from(error: LeNLPError) -> Self {
+        match error {
+            LeNLPError::Io(_) => PyIOError::new_err(error.to_string()),
+            _ => PyValueError::new_err(error.to_string()),
+        }
+    }
+}
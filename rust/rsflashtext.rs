@@ -1,22 +1,176 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 use std::collections::HashMap;
 use std::collections::HashSet;
 
 use rayon::prelude::*;
-use unidecode::unidecode;
+use std::str::FromStr;
 
-#[pyclass()]
+use crate::rserror::LeNLPError;
+
+/// Characters considered part of a word, keywords only match between two
+/// characters outside of words.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WordBoundaries {
+    /// Words are made of `[0-9a-zA-Z_]`.
+    #[default]
+    Ascii,
+    /// Words are made of Unicode alphanumeric characters and `_`, so that accented,
+    /// Cyrillic or Greek keywords match as whole words.
+    Unicode,
+}
+
+impl FromStr for WordBoundaries {
+    type Err = LeNLPError;
+
+    fn from_str(word_boundaries: &str) -> Result<Self, Self::Err> {
+        match word_boundaries {
+            "ascii" => Ok(WordBoundaries::Ascii),
+            "unicode" => Ok(WordBoundaries::Unicode),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid word_boundaries '{}', expected 'ascii' or 'unicode'.",
+                word_boundaries
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for WordBoundaries {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<WordBoundaries>()?)
+    }
+}
+
+/// Which of the keywords starting at the same word are extracted.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum MatchPolicy {
+    /// Leftmost-longest: the longest keyword starting at the word, the scan resumes
+    /// after it. With "new york" and "new york city hall", "new york city" gives
+    /// "new york".
+    #[default]
+    Longest,
+    /// Every keyword starting at every word, keywords may overlap.
+    Overlapping,
+    /// The shortest keyword starting at the word, the scan resumes after it.
+    First,
+}
+
+impl FromStr for MatchPolicy {
+    type Err = LeNLPError;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "longest" => Ok(MatchPolicy::Longest),
+            "overlapping" => Ok(MatchPolicy::Overlapping),
+            "first" => Ok(MatchPolicy::First),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid policy '{}', expected 'longest', 'overlapping' or 'first'.",
+                policy
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for MatchPolicy {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<MatchPolicy>()?)
+    }
+}
+
+/// Keyword found in a sentence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeywordMatch {
+    pub clean_name: String,
+    pub start: usize,
+    pub end: usize,
+    /// Edit distance between the keyword and the matched text, 0 for exact matches.
+    pub distance: usize,
+}
+
+/// Keyword as returned to Python: its clean name, start and end offsets, followed
+/// by its edit distance when details are requested.
+#[cfg(feature = "python")]
+#[derive(IntoPyObject)]
+enum PyKeywordMatch {
+    Span((String, usize, usize)),
+    Details((String, usize, usize, usize)),
+}
+
+#[cfg(feature = "python")]
+impl PyKeywordMatch {
+    fn new(keyword: KeywordMatch, return_details: bool) -> Self {
+        if return_details {
+            PyKeywordMatch::Details((
+                keyword.clean_name,
+                keyword.start,
+                keyword.end,
+                keyword.distance,
+            ))
+        } else {
+            PyKeywordMatch::Span((keyword.clean_name, keyword.start, keyword.end))
+        }
+    }
+}
+
+/// Unit of the start and end offsets of extracted keywords.
+///
+/// Offsets count characters unless byte offsets are requested, in Rust as in the
+/// Python bindings and the `FlashText` wrapper.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Offsets {
+    /// Offsets index the UTF-8 bytes of the sentence, as Rust string slicing.
+    Byte,
+    /// Offsets count Unicode code points, as Python string slicing.
+    #[default]
+    Char,
+}
+
+impl Offsets {
+    fn of(&self, position: Position) -> usize {
+        match self {
+            Offsets::Byte => position.byte,
+            Offsets::Char => position.char,
+        }
+    }
+}
+
+impl FromStr for Offsets {
+    type Err = LeNLPError;
+
+    fn from_str(offsets: &str) -> Result<Self, Self::Err> {
+        match offsets {
+            "byte" => Ok(Offsets::Byte),
+            "char" => Ok(Offsets::Char),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid offsets '{}', expected 'byte' or 'char'.",
+                offsets
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Offsets {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<Offsets>()?)
+    }
+}
+
+#[cfg_attr(feature = "python", pyclass())]
 pub struct RSKeywordProcessor {
-    keyword: String,
+    /// Characters part of words on top of those of `word_boundaries`.
     non_word_boundaries: HashSet<char>,
+    word_boundaries: WordBoundaries,
     keyword_trie_dict: HashMap<char, RSTrieNode>,
     lowercase: bool,
     normalize: bool,
     terms_in_trie: usize,
 }
 
-#[pyclass()]
+#[cfg_attr(feature = "python", pyclass())]
 pub struct RSTrieNode {
     children: HashMap<char, RSTrieNode>,
     is_end: bool,
@@ -33,23 +187,17 @@ impl RSTrieNode {
     }
 }
 
-#[pymethods]
-impl RSKeywordProcessor {
-    #[new]
-    pub fn new(lowercase: bool, normalize: bool) -> Self {
-        let keyword: String = "_keyword_".to_string();
-        let non_word_boundaries: HashSet<char> = {
-            let mut set: HashSet<char> = HashSet::new();
-            set.extend('0'..='9');
-            set.extend('a'..='z');
-            set.extend('A'..='Z');
-            set.insert('_');
-            set
-        };
+impl Default for RSTrieNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+impl RSKeywordProcessor {
+    pub fn new(lowercase: bool, normalize: bool, word_boundaries: WordBoundaries) -> Self {
         RSKeywordProcessor {
-            keyword,
-            non_word_boundaries,
+            non_word_boundaries: HashSet::new(),
+            word_boundaries,
             keyword_trie_dict: HashMap::new(),
             lowercase,
             normalize,
@@ -57,6 +205,28 @@ impl RSKeywordProcessor {
         }
     }
 
+    /// Treat a character, such as '-' or '.', as part of keywords rather than as a
+    /// word boundary. With `normalize`, ASCII punctuation is removed before matching.
+    pub fn add_non_word_boundary(&mut self, character: char) {
+        self.non_word_boundaries.insert(character);
+    }
+
+    /// Replace the characters added with `add_non_word_boundary`, the characters of
+    /// the word boundaries mode stay part of words.
+    pub fn set_non_word_boundaries(&mut self, characters: Vec<char>) {
+        self.non_word_boundaries = characters.into_iter().collect();
+    }
+
+    /// Whether a character continues a word rather than separating two words.
+    fn is_word_char(&self, character: char) -> bool {
+        character == '_'
+            || self.non_word_boundaries.contains(&character)
+            || match self.word_boundaries {
+                WordBoundaries::Ascii => character.is_ascii_alphanumeric(),
+                WordBoundaries::Unicode => character.is_alphanumeric(),
+            }
+    }
+
     pub fn add_keywords_many(
         &mut self,
         keywords: Vec<String>,
@@ -64,139 +234,872 @@ impl RSKeywordProcessor {
     ) -> Vec<bool> {
         keywords
             .iter()
-            .map(|keyword: &String| self.add_keyword(&keyword, clean_name))
+            .map(|keyword: &String| self.add_keyword(keyword, clean_name))
             .collect()
     }
 
     pub fn add_keyword(&mut self, keyword: &str, clean_name: Option<&str>) -> bool {
         let clean_name: &str = clean_name.unwrap_or(keyword);
-        let keyword: String = if self.normalize {
-            unidecode(keyword)
-                .to_lowercase()
-                .chars()
-                .filter(|c| !c.is_ascii_punctuation())
-                .collect::<String>()
-                .trim()
-                .to_string()
-        } else if self.lowercase {
-            keyword.to_lowercase()
-        } else {
-            keyword.to_string()
+        let keyword: String = self._normalize_keyword(keyword);
+
+        let mut chars = keyword.chars();
+        let Some(first_char) = chars.next() else {
+            return false;
         };
+        let mut current_node: &mut RSTrieNode =
+            self.keyword_trie_dict.entry(first_char).or_default();
+        for char in chars {
+            current_node = current_node.children.entry(char).or_default();
+        }
+
+        if current_node.is_end {
+            return false;
+        }
+        current_node.is_end = true;
+        current_node.clean_name = Some(clean_name.to_string());
+        self.terms_in_trie += 1;
+        true
+    }
+
+    pub fn remove_keywords_many(&mut self, keywords: Vec<String>) -> Vec<bool> {
+        keywords
+            .iter()
+            .map(|keyword: &String| self.remove_keyword(keyword))
+            .collect()
+    }
+
+    /// Remove a keyword and the branches of the trie left without keyword. Return
+    /// whether the keyword was found.
+    pub fn remove_keyword(&mut self, keyword: &str) -> bool {
+        let keyword: Vec<char> = self._normalize_keyword(keyword).chars().collect();
+
+        let removed: bool = remove_branch(&mut self.keyword_trie_dict, &keyword);
+        if removed {
+            self.terms_in_trie -= 1;
+        }
+        removed
+    }
 
-        let mut current_node: &mut HashMap<char, RSTrieNode> = &mut self.keyword_trie_dict;
+    /// Number of keywords in the trie.
+    pub fn len(&self) -> usize {
+        self.terms_in_trie
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms_in_trie == 0
+    }
+
+    pub fn contains(&self, keyword: &str) -> bool {
+        self.get_keyword(keyword).is_some()
+    }
+
+    /// Clean name of a keyword, None if the keyword is not in the trie.
+    pub fn get_keyword(&self, keyword: &str) -> Option<String> {
+        let mut current_node: &HashMap<char, RSTrieNode> = &self.keyword_trie_dict;
+        let mut terminal: Option<&RSTrieNode> = None;
+        for char in self._normalize_keyword(keyword).chars() {
+            let node: &RSTrieNode = current_node.get(&char)?;
+            current_node = &node.children;
+            terminal = Some(node);
+        }
+
+        terminal
+            .filter(|node: &&RSTrieNode| node.is_end)
+            .and_then(|node: &RSTrieNode| node.clean_name.clone())
+    }
+
+    /// Mapping of every keyword of the trie, as stored after normalization, to its
+    /// clean name.
+    pub fn get_all_keywords(&self) -> HashMap<String, String> {
+        let mut keywords: HashMap<String, String> = HashMap::new();
+        collect_keywords(&self.keyword_trie_dict, &mut String::new(), &mut keywords);
+        keywords
+    }
+
+    /// Apply the normalization of the sentences to a keyword, character by character.
+    fn _normalize_keyword(&self, keyword: &str) -> String {
+        let mut normalized: String = String::with_capacity(keyword.len());
         for char in keyword.chars() {
-            current_node = &mut current_node
-                .entry(char)
-                .or_insert_with(RSTrieNode::new)
-                .children;
-        }
-
-        if !current_node.contains_key(&self.keyword.chars().next().unwrap()) {
-            self.terms_in_trie += 1;
-            current_node.insert(
-                self.keyword.chars().next().unwrap(),
-                RSTrieNode {
-                    children: HashMap::new(),
-                    is_end: true,
-                    clean_name: Some(clean_name.to_string()),
-                },
-            );
-            true
+            self.normalize_char(char, |normalized_char: char| {
+                normalized.push(normalized_char)
+            });
+        }
+        if self.normalize {
+            normalized.trim().to_string()
         } else {
-            false
+            normalized
         }
     }
 
     pub fn extract_keywords_many(
         &self,
         sentences: Vec<String>,
-    ) -> Vec<Vec<(String, usize, usize)>> {
+        offsets: Offsets,
+        policy: MatchPolicy,
+        max_cost: usize,
+    ) -> Vec<Vec<KeywordMatch>> {
         sentences
             .par_iter()
-            .map(|sentence: &String| self.extract_keywords(&sentence))
+            .map(|sentence: &String| self.extract_keywords(sentence, offsets, policy, max_cost))
             .collect()
     }
 
-    pub fn extract_keywords(&self, sentence: &str) -> Vec<(String, usize, usize)> {
-        // Map from the index in the normalized sentence to the index in the original sentence
-        let mut index_map: Vec<usize> = Vec::with_capacity(sentence.len());
-        let mut original_idx = 0;
+    /// Return the keywords of a sentence with their start and end offsets.
+    ///
+    /// The sentence is normalized in a single pass over its characters, each
+    /// normalized character keeping the position of the original character it comes
+    /// from, so that offsets always index the original sentence. Matches start and
+    /// end at word boundaries and may span several words, the policy decides which of
+    /// the keywords starting at each word are kept.
+    ///
+    /// With a `max_cost` above 0, keywords also match text within that many character
+    /// insertions, deletions or substitutions, so that "machin learning" gives
+    /// "machine learning" with a distance of 1.
+    pub fn extract_keywords(
+        &self,
+        sentence: &str,
+        offsets: Offsets,
+        policy: MatchPolicy,
+        max_cost: usize,
+    ) -> Vec<KeywordMatch> {
+        let chars: Vec<NormalizedChar> = self._normalize_sentence(sentence);
 
-        let normalized_sentence: String = if self.normalize {
-            let mut normalized = String::new();
-            for c in sentence.chars() {
-                if c.is_ascii_punctuation() {
-                    original_idx += c.len_utf8();
-                    continue;
-                }
-                let normalized_char = unidecode::unidecode_char(c).to_lowercase();
-                for nc in normalized_char.chars() {
-                    normalized.push(nc);
-                    index_map.push(original_idx);
+        let mut extracted_keywords: Vec<KeywordMatch> = Vec::new();
+        let mut idx: usize = 0;
+        while idx < chars.len() {
+            let is_word_start: bool = chars[idx].is_word && (idx == 0 || !chars[idx - 1].is_word);
+            if !is_word_start {
+                idx += 1;
+                continue;
+            }
+
+            let matches: Vec<(usize, usize, &String)> = if max_cost == 0 {
+                self._match_from(&chars, idx)
+            } else {
+                self._fuzzy_match_from(&chars, idx, max_cost)
+            };
+            let selected: &[(usize, usize, &String)] = match policy {
+                MatchPolicy::Longest => &matches[matches.len().saturating_sub(1)..],
+                MatchPolicy::First => &matches[..matches.len().min(1)],
+                MatchPolicy::Overlapping => &matches,
+            };
+            for &(end, distance, clean_name) in selected {
+                extracted_keywords.push(KeywordMatch {
+                    clean_name: clean_name.clone(),
+                    start: offsets.of(chars[idx].start),
+                    end: offsets.of(chars[end - 1].end),
+                    distance,
+                });
+            }
+
+            // Non-overlapping policies resume after the selected keyword.
+            idx = match (policy, selected.last()) {
+                (MatchPolicy::Longest | MatchPolicy::First, Some(&(end, _, _))) => end,
+                _ => idx + 1,
+            };
+        }
+
+        extracted_keywords
+    }
+
+    /// Walk the trie from the word starting at `start` and return the end index, edit
+    /// distance and clean name of every keyword ending at a word boundary, by
+    /// increasing length.
+    /// The walk goes on after each complete keyword so that the longest policy falls
+    /// back to the last complete keyword when a longer branch fails.
+    fn _match_from<'a>(
+        &'a self,
+        chars: &[NormalizedChar],
+        start: usize,
+    ) -> Vec<(usize, usize, &'a String)> {
+        let mut matches: Vec<(usize, usize, &String)> = Vec::new();
+        let mut current_node: &HashMap<char, RSTrieNode> = &self.keyword_trie_dict;
+        for idx in start..chars.len() {
+            let Some(node) = current_node.get(&chars[idx].char) else {
+                break;
+            };
+            current_node = &node.children;
+
+            if !is_word_end(chars, idx + 1) {
+                continue;
+            }
+            if let Some(clean_name) = node.clean_name.as_ref().filter(|_| node.is_end) {
+                matches.push((idx + 1, 0, clean_name));
+            }
+        }
+        matches
+    }
+
+    /// Same as `_match_from` with up to `max_cost` edits between each keyword and the
+    /// text it matches. Each keyword is matched once, at its cheapest end, ties going
+    /// to the longest span.
+    fn _fuzzy_match_from<'a>(
+        &'a self,
+        chars: &[NormalizedChar],
+        start: usize,
+        max_cost: usize,
+    ) -> Vec<(usize, usize, &'a String)> {
+        let text: &[NormalizedChar] = &chars[start..];
+        // Distances between the empty keyword prefix and the first characters of text.
+        let row: Vec<usize> = (0..=text.len().min(max_cost)).collect();
+        let mut matches: Vec<(usize, usize, &String)> = Vec::new();
+        fuzzy_walk(&self.keyword_trie_dict, text, &row, max_cost, &mut matches);
+        matches.sort_by_key(|&(end, distance, _)| (end, distance));
+        matches
+            .into_iter()
+            .map(|(end, distance, clean_name)| (start + end, distance, clean_name))
+            .collect()
+    }
+
+    /// Normalize a sentence in a single pass, keeping the position of the original
+    /// character each normalized character comes from.
+    fn _normalize_sentence(&self, sentence: &str) -> Vec<NormalizedChar> {
+        let mut chars: Vec<NormalizedChar> = Vec::with_capacity(sentence.len());
+        for (char_idx, (byte_idx, char)) in sentence.char_indices().enumerate() {
+            let start: Position = Position {
+                byte: byte_idx,
+                char: char_idx,
+            };
+            let end: Position = Position {
+                byte: byte_idx + char.len_utf8(),
+                char: char_idx + 1,
+            };
+            self.normalize_char(char, |normalized_char: char| {
+                chars.push(NormalizedChar {
+                    char: normalized_char,
+                    is_word: self.is_word_char(normalized_char),
+                    start,
+                    end,
+                })
+            });
+        }
+        chars
+    }
+
+    /// Feed the characters a keyword or sentence character normalizes to. With
+    /// `normalize`, ASCII punctuation is removed after transliteration, so that `’`
+    /// or `«` are dropped like `'` or `"`.
+    fn normalize_char(&self, char: char, mut feed: impl FnMut(char)) {
+        if self.normalize {
+            if char.is_ascii() {
+                if !char.is_ascii_punctuation() {
+                    feed(char.to_ascii_lowercase());
                 }
-                original_idx += c.len_utf8();
+            } else {
+                unidecode::unidecode_char(char)
+                    .chars()
+                    .filter(|c| !c.is_ascii_punctuation())
+                    .flat_map(char::to_lowercase)
+                    .for_each(feed);
             }
-            normalized.to_string()
         } else if self.lowercase {
-            sentence.to_lowercase()
+            char.to_lowercase().for_each(feed);
         } else {
-            sentence.to_string()
-        };
+            feed(char);
+        }
+    }
 
-        let mut extracted_keywords: Vec<(String, usize, usize)> = Vec::new();
-        let mut current_node: &HashMap<char, RSTrieNode> = &self.keyword_trie_dict;
-        let mut start_pos: usize = 0;
-        let mut end_pos: usize = 0;
+    pub fn replace_keywords_many(&self, sentences: Vec<String>) -> Vec<String> {
+        sentences
+            .par_iter()
+            .map(|sentence: &String| self.replace_keywords(sentence))
+            .collect()
+    }
 
-        let mut idx: usize = 0;
-        let sentence_len: usize = normalized_sentence.len();
-        while idx < sentence_len {
-            let char: char = normalized_sentence.chars().nth(idx).unwrap();
-            if !self.non_word_boundaries.contains(&char) {
-                if let Some(node) = current_node.get(&self.keyword.chars().next().unwrap()) {
-                    if node.is_end {
-                        let clean_name: &String = node.clean_name.as_ref().unwrap();
-                        let original_start_pos = index_map[start_pos];
-                        let original_end_pos = index_map[end_pos - 1] + 1;
-                        extracted_keywords.push((
-                            clean_name.clone(),
-                            original_start_pos,
-                            original_end_pos,
-                        ));
-                    }
+    /// Rewrite each keyword found by `extract_keywords` with the longest policy with
+    /// its clean name, the text between keywords is kept unchanged.
+    pub fn replace_keywords(&self, sentence: &str) -> String {
+        let mut replaced: String = String::with_capacity(sentence.len());
+        let mut last_end: usize = 0;
+        for keyword in self.extract_keywords(sentence, Offsets::Byte, MatchPolicy::Longest, 0) {
+            replaced.push_str(&sentence[last_end..keyword.start]);
+            replaced.push_str(&keyword.clean_name);
+            last_end = keyword.end;
+        }
+        replaced.push_str(&sentence[last_end..]);
+        replaced
+    }
+}
+
+/// Unmark the terminal node of a keyword below `node`, then remove the nodes left
+/// without keyword on the way back up.
+fn remove_branch(node: &mut HashMap<char, RSTrieNode>, keyword: &[char]) -> bool {
+    let Some((&char, rest)) = keyword.split_first() else {
+        return false;
+    };
+    let Some(child) = node.get_mut(&char) else {
+        return false;
+    };
+
+    let removed: bool = if rest.is_empty() {
+        let is_end: bool = child.is_end;
+        child.is_end = false;
+        child.clean_name = None;
+        is_end
+    } else {
+        remove_branch(&mut child.children, rest)
+    };
+    if removed && child.children.is_empty() && !child.is_end {
+        node.remove(&char);
+    }
+    removed
+}
+
+/// Whether the character before `end` closes a word.
+fn is_word_end(chars: &[NormalizedChar], end: usize) -> bool {
+    chars[end - 1].is_word && chars.get(end).is_none_or(|next| !next.is_word)
+}
+
+/// Depth-first walk of the trie computing the Levenshtein distances between the
+/// keyword prefix of each node and the first characters of `text`.
+///
+/// `row[j]` is the distance between the prefix of `node` and `text[..j]`. Past
+/// `max_cost + depth` characters the distance exceeds the budget, so rows grow by one
+/// column per level and branches are pruned once every distance of the row does.
+fn fuzzy_walk<'a>(
+    node: &'a HashMap<char, RSTrieNode>,
+    text: &[NormalizedChar],
+    row: &[usize],
+    max_cost: usize,
+    matches: &mut Vec<(usize, usize, &'a String)>,
+) {
+    for (&char, child) in node.iter() {
+        let n_cols: usize = (row.len() + 1).min(text.len() + 1);
+        let mut next: Vec<usize> = Vec::with_capacity(n_cols);
+        next.push(row[0] + 1);
+        for j in 1..n_cols {
+            let substitution: usize = row[j - 1] + usize::from(text[j - 1].char != char);
+            let deletion: usize = row.get(j).map_or(usize::MAX, |&distance| distance + 1);
+            let insertion: usize = next[j - 1] + 1;
+            next.push(substitution.min(deletion).min(insertion));
+        }
+
+        if child.is_end {
+            if let Some(clean_name) = &child.clean_name {
+                let best: Option<usize> = (1..next.len())
+                    .filter(|&end| next[end] <= max_cost && is_word_end(text, end))
+                    .min_by_key(|&end| (next[end], std::cmp::Reverse(end)));
+                if let Some(end) = best {
+                    matches.push((end, next[end], clean_name));
                 }
-                current_node = &self.keyword_trie_dict;
-                start_pos = idx + 1;
-            } else if let Some(node) = current_node.get(&char) {
-                current_node = &node.children;
-                end_pos = idx + 1;
-            } else {
-                current_node = &self.keyword_trie_dict;
-                start_pos = idx + 1;
             }
-            idx += 1;
         }
+        if next.iter().any(|&distance| distance <= max_cost) {
+            fuzzy_walk(&child.children, text, &next, max_cost, matches);
+        }
+    }
+}
 
-        // Check if the last segment is a keyword
-        if let Some(node) = current_node.get(&self.keyword.chars().next().unwrap()) {
-            if node.is_end {
-                let clean_name: &String = node.clean_name.as_ref().unwrap();
-                let original_start_pos = index_map[start_pos];
-                let original_end_pos = index_map[end_pos - 1] + 1;
-                extracted_keywords.push((clean_name.clone(), original_start_pos, original_end_pos));
+/// Depth-first walk of the trie collecting each keyword and its clean name.
+fn collect_keywords(
+    node: &HashMap<char, RSTrieNode>,
+    prefix: &mut String,
+    keywords: &mut HashMap<String, String>,
+) {
+    for (&char, child) in node.iter() {
+        prefix.push(char);
+        if child.is_end {
+            if let Some(clean_name) = &child.clean_name {
+                keywords.insert(prefix.clone(), clean_name.clone());
             }
         }
+        collect_keywords(&child.children, prefix, keywords);
+        prefix.pop();
+    }
+}
 
-        extracted_keywords
+/// Position of a character in a sentence.
+#[derive(Clone, Copy, Debug, Default)]
+struct Position {
+    byte: usize,
+    char: usize,
+}
+
+/// Character of a normalized sentence and position of the original character it
+/// comes from.
+struct NormalizedChar {
+    char: char,
+    is_word: bool,
+    start: Position,
+    end: Position,
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl RSKeywordProcessor {
+    #[new]
+    #[pyo3(signature = (lowercase, normalize, word_boundaries=WordBoundaries::Ascii))]
+    fn py_new(lowercase: bool, normalize: bool, word_boundaries: WordBoundaries) -> Self {
+        Self::new(lowercase, normalize, word_boundaries)
+    }
+
+    #[pyo3(name = "add_non_word_boundary")]
+    fn py_add_non_word_boundary(&mut self, character: char) {
+        self.add_non_word_boundary(character)
+    }
+
+    #[pyo3(name = "set_non_word_boundaries")]
+    fn py_set_non_word_boundaries(&mut self, characters: Vec<char>) {
+        self.set_non_word_boundaries(characters)
+    }
+
+    #[pyo3(name = "add_keywords_many")]
+    fn py_add_keywords_many(
+        &mut self,
+        keywords: Vec<String>,
+        clean_name: Option<&str>,
+    ) -> Vec<bool> {
+        self.add_keywords_many(keywords, clean_name)
+    }
+
+    #[pyo3(name = "add_keyword")]
+    fn py_add_keyword(&mut self, keyword: &str, clean_name: Option<&str>) -> bool {
+        self.add_keyword(keyword, clean_name)
+    }
+
+    #[pyo3(name = "remove_keywords_many")]
+    fn py_remove_keywords_many(&mut self, keywords: Vec<String>) -> Vec<bool> {
+        self.remove_keywords_many(keywords)
+    }
+
+    #[pyo3(name = "remove_keyword")]
+    fn py_remove_keyword(&mut self, keyword: &str) -> bool {
+        self.remove_keyword(keyword)
+    }
+
+    #[pyo3(name = "get_keyword")]
+    fn py_get_keyword(&self, keyword: &str) -> Option<String> {
+        self.get_keyword(keyword)
+    }
+
+    #[pyo3(name = "get_all_keywords")]
+    fn py_get_all_keywords(&self) -> HashMap<String, String> {
+        self.get_all_keywords()
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __contains__(&self, keyword: &str) -> bool {
+        self.contains(keyword)
+    }
+
+    #[pyo3(
+        name = "extract_keywords_many",
+        signature = (sentences, offsets=Offsets::default(), policy=MatchPolicy::Longest, max_cost=0, return_details=false)
+    )]
+    fn py_extract_keywords_many(
+        &self,
+        sentences: Vec<String>,
+        offsets: Offsets,
+        policy: MatchPolicy,
+        max_cost: usize,
+        return_details: bool,
+        py: Python<'_>,
+    ) -> Vec<Vec<PyKeywordMatch>> {
+        py.allow_threads(|| self.extract_keywords_many(sentences, offsets, policy, max_cost))
+            .into_iter()
+            .map(|keywords| {
+                keywords
+                    .into_iter()
+                    .map(|keyword| PyKeywordMatch::new(keyword, return_details))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[pyo3(
+        name = "extract_keywords",
+        signature = (sentence, offsets=Offsets::default(), policy=MatchPolicy::Longest, max_cost=0, return_details=false)
+    )]
+    fn py_extract_keywords(
+        &self,
+        sentence: &str,
+        offsets: Offsets,
+        policy: MatchPolicy,
+        max_cost: usize,
+        return_details: bool,
+    ) -> Vec<PyKeywordMatch> {
+        self.extract_keywords(sentence, offsets, policy, max_cost)
+            .into_iter()
+            .map(|keyword| PyKeywordMatch::new(keyword, return_details))
+            .collect()
+    }
+
+    #[pyo3(name = "replace_keywords_many")]
+    fn py_replace_keywords_many(&self, sentences: Vec<String>) -> Vec<String> {
+        self.replace_keywords_many(sentences)
+    }
+
+    #[pyo3(name = "replace_keywords")]
+    fn py_replace_keywords(&self, sentence: &str) -> String {
+        self.replace_keywords(sentence)
     }
 }
 
 /// Registers all the above functions in a Python sub-module.
 ///
 /// Called from your `#[pymodule]` entry-point.
+#[cfg(feature = "python")]
 pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<RSKeywordProcessor>()?;
     m.add_class::<RSTrieNode>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(keywords: Vec<KeywordMatch>) -> Vec<(String, usize, usize)> {
+        keywords
+            .into_iter()
+            .map(|keyword| (keyword.clean_name, keyword.start, keyword.end))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_keywords_without_normalization() {
+        let mut processor = RSKeywordProcessor::new(true, false, WordBoundaries::Ascii);
+        processor.add_keyword("hello", None);
+
+        assert_eq!(
+            spans(processor.extract_keywords(
+                "Hello world, hello",
+                Offsets::Byte,
+                MatchPolicy::Longest,
+                0
+            )),
+            vec![("hello".to_string(), 0, 5), ("hello".to_string(), 13, 18)]
+        );
+        assert_eq!(
+            spans(processor.extract_keywords(
+                "Ωmega hello",
+                Offsets::Byte,
+                MatchPolicy::Longest,
+                0
+            )),
+            vec![("hello".to_string(), 7, 12)]
+        );
+    }
+
+    #[test]
+    fn test_extract_keywords_offsets() {
+        let sentence: &str = "Ça, c'est l'Œuvre de Ærø à Zürich; zürichsee.";
+        let slices = |processor: &RSKeywordProcessor, offsets: Offsets| -> Vec<String> {
+            processor
+                .extract_keywords(sentence, offsets, MatchPolicy::Longest, 0)
+                .into_iter()
+                .map(|KeywordMatch { start, end, .. }| match offsets {
+                    Offsets::Byte => sentence[start..end].to_string(),
+                    Offsets::Char => sentence.chars().skip(start).take(end - start).collect(),
+                })
+                .collect()
+        };
+
+        // Raw, lowercased and normalized sentences map back to the same spans.
+        let mut raw = RSKeywordProcessor::new(false, false, WordBoundaries::Unicode);
+        raw.add_keywords_many(vec!["Œuvre".to_string(), "Zürich".to_string()], None);
+        let mut lowercase = RSKeywordProcessor::new(true, false, WordBoundaries::Unicode);
+        lowercase.add_keywords_many(vec!["œuvre".to_string(), "zürich".to_string()], None);
+        let mut normalize = RSKeywordProcessor::new(true, true, WordBoundaries::Ascii);
+        normalize.add_keywords_many(
+            vec![
+                "oeuvre".to_string(),
+                "zurich".to_string(),
+                "aero".to_string(),
+            ],
+            None,
+        );
+
+        for offsets in [Offsets::Byte, Offsets::Char] {
+            assert_eq!(slices(&raw, offsets), ["Œuvre", "Zürich"]);
+            assert_eq!(slices(&lowercase, offsets), ["Œuvre", "Zürich"]);
+            // Normalization removes the apostrophe, "l'Œuvre" is a single word.
+            assert_eq!(slices(&normalize, offsets), ["Ærø", "Zürich"]);
+        }
+        assert_eq!(
+            spans(raw.extract_keywords(sentence, Offsets::Char, MatchPolicy::Longest, 0)),
+            vec![
+                ("Œuvre".to_string(), 12, 17),
+                ("Zürich".to_string(), 27, 33)
+            ]
+        );
+
+        // The Python wrapper defaults to "char" as well.
+        assert_eq!("char".parse::<Offsets>().unwrap(), Offsets::default());
+
+        // Keywords only match whole words, even after a mismatch inside the word.
+        let mut processor = RSKeywordProcessor::new(true, false, WordBoundaries::Ascii);
+        processor.add_keyword("ello", None);
+        assert!(processor
+            .extract_keywords("hello", Offsets::Byte, MatchPolicy::Longest, 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_normalize_non_ascii_punctuation() {
+        // Transliterated punctuation is removed from keywords and sentences alike.
+        let sentence: &str = "J'écoute du Rock ’n’ Roll, je lis «Le Monde» et Paris–Roubaix.";
+        let mut processor = RSKeywordProcessor::new(true, true, WordBoundaries::Ascii);
+        processor.add_keywords_many(
+            vec![
+                "rock ’n’ roll".to_string(),
+                "«le monde»".to_string(),
+                "Paris–Roubaix".to_string(),
+            ],
+            None,
+        );
+
+        let slices: Vec<&str> = processor
+            .extract_keywords(sentence, Offsets::Byte, MatchPolicy::Longest, 0)
+            .into_iter()
+            .map(|KeywordMatch { start, end, .. }| &sentence[start..end])
+            .collect();
+        assert_eq!(slices, ["Rock ’n’ Roll", "Le Monde", "Paris–Roubaix"]);
+        assert_eq!(
+            processor.get_keyword("rock 'n' roll"),
+            Some("rock ’n’ roll".to_string())
+        );
+    }
+
+    #[test]
+    fn test_remove_keywords() {
+        let mut processor = RSKeywordProcessor::new(true, false, WordBoundaries::Ascii);
+        processor.add_keywords_many(
+            vec![
+                "rust".to_string(),
+                "rustacean".to_string(),
+                "python".to_string(),
+            ],
+            None,
+        );
+        processor.add_keyword("py", Some("python"));
+        assert_eq!(processor.len(), 4);
+        assert!(processor.contains("Rust"));
+        assert_eq!(processor.get_keyword("py"), Some("python".to_string()));
+
+        assert_eq!(
+            processor.remove_keywords_many(vec!["rust".to_string(), "java".to_string()]),
+            vec![true, false]
+        );
+        assert!(!processor.remove_keyword("rust"));
+        assert_eq!(processor.len(), 3);
+        assert!(!processor.contains("rust"));
+        assert!(processor.contains("rustacean"));
+
+        assert!(processor.remove_keyword("rustacean"));
+        assert!(!processor.keyword_trie_dict.contains_key(&'r'));
+        assert_eq!(
+            processor.get_all_keywords(),
+            HashMap::from([
+                ("python".to_string(), "python".to_string()),
+                ("py".to_string(), "python".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_keywords_with_underscores() {
+        let mut processor = RSKeywordProcessor::new(true, false, WordBoundaries::Ascii);
+        assert!(processor.add_keyword("a_b", None));
+        assert!(processor.add_keyword("a", None));
+        assert!(!processor.add_keyword("a", None));
+        assert!(!processor.add_keyword("", None));
+        assert_eq!(processor.len(), 2);
+        assert!(processor.contains("a"));
+        assert_eq!(
+            spans(processor.extract_keywords("a a_b", Offsets::Char, MatchPolicy::Longest, 0)),
+            [("a".to_string(), 0, 1), ("a_b".to_string(), 2, 5)]
+        );
+        assert_eq!(
+            spans(processor.extract_keywords("a_c", Offsets::Char, MatchPolicy::Longest, 1)),
+            [("a_b".to_string(), 0, 3)]
+        );
+
+        assert!(processor.remove_keyword("a"));
+        assert!(!processor.contains("a"));
+        assert_eq!(
+            processor.get_all_keywords(),
+            HashMap::from([("a_b".to_string(), "a_b".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let mut processor = RSKeywordProcessor::new(false, false, WordBoundaries::Ascii);
+        processor.add_keywords_many(vec!["café".to_string(), "Москва".to_string()], None);
+        assert!(processor
+            .extract_keywords("un café à Москва", Offsets::Byte, MatchPolicy::Longest, 0)
+            .is_empty());
+
+        let mut processor = RSKeywordProcessor::new(false, false, "unicode".parse().unwrap());
+        processor.add_keywords_many(
+            vec!["café".to_string(), "Москва".to_string(), "mail".to_string()],
+            None,
+        );
+        let keywords = |processor: &RSKeywordProcessor, sentence: &str| -> Vec<String> {
+            processor
+                .extract_keywords(sentence, Offsets::Byte, MatchPolicy::Longest, 0)
+                .into_iter()
+                .map(|keyword| keyword.clean_name)
+                .collect()
+        };
+        assert_eq!(keywords(&processor, "un café à Москва"), ["café", "Москва"]);
+        assert!(keywords(&processor, "cafés").is_empty());
+
+        // '-' separates words until it is made part of words.
+        assert_eq!(keywords(&processor, "send an e-mail"), ["mail"]);
+        processor.add_non_word_boundary('-');
+        assert!(keywords(&processor, "send an e-mail").is_empty());
+        processor.set_non_word_boundaries(vec![]);
+        assert_eq!(keywords(&processor, "send an e-mail"), ["mail"]);
+    }
+
+    #[test]
+    fn test_set_non_word_boundaries_keeps_ascii_words() {
+        let mut processor = RSKeywordProcessor::new(true, false, WordBoundaries::Ascii);
+        processor.add_keywords_many(vec!["hello".to_string(), "e-mail".to_string()], None);
+        processor.set_non_word_boundaries(vec!['-']);
+        assert_eq!(
+            spans(processor.extract_keywords(
+                "hello e-mail",
+                Offsets::Char,
+                MatchPolicy::Longest,
+                0
+            )),
+            [("hello".to_string(), 0, 5), ("e-mail".to_string(), 6, 12)]
+        );
+
+        // '-' joins words, so "hello" is no longer a whole word in "hello-world".
+        assert!(processor
+            .extract_keywords("hello-world", Offsets::Char, MatchPolicy::Longest, 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_match_policies() {
+        let mut processor = RSKeywordProcessor::new(true, true, WordBoundaries::Ascii);
+        processor.add_keywords_many(
+            vec![
+                "new york".to_string(),
+                "york city".to_string(),
+                "new york city hall".to_string(),
+                "city".to_string(),
+            ],
+            None,
+        );
+        let extract = |sentence: &str, policy: MatchPolicy| -> Vec<(String, usize, usize)> {
+            spans(processor.extract_keywords(sentence, Offsets::Char, policy, 0))
+        };
+        let keyword = |clean_name: &str, start: usize, end: usize| -> (String, usize, usize) {
+            (clean_name.to_string(), start, end)
+        };
+
+        // "new york city hall" fails at "new york city", the longest policy falls back
+        // to "new york" and resumes at "city".
+        let sentence: &str = "New York city, new york city hall";
+        assert_eq!(
+            extract(sentence, MatchPolicy::Longest),
+            [
+                keyword("new york", 0, 8),
+                keyword("city", 9, 13),
+                keyword("new york city hall", 15, 33)
+            ]
+        );
+        assert_eq!(
+            extract(sentence, MatchPolicy::First),
+            [
+                keyword("new york", 0, 8),
+                keyword("city", 9, 13),
+                keyword("new york", 15, 23),
+                keyword("city", 24, 28)
+            ]
+        );
+        assert_eq!(
+            extract(sentence, MatchPolicy::Overlapping),
+            [
+                keyword("new york", 0, 8),
+                keyword("york city", 4, 13),
+                keyword("city", 9, 13),
+                keyword("new york", 15, 23),
+                keyword("new york city hall", 15, 33),
+                keyword("york city", 19, 28),
+                keyword("city", 24, 28)
+            ]
+        );
+
+        assert_eq!(
+            extract(sentence, "overlapping".parse().unwrap()),
+            extract(sentence, MatchPolicy::Overlapping)
+        );
+        assert!("shortest".parse::<MatchPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_matching() {
+        let mut processor = RSKeywordProcessor::new(true, true, WordBoundaries::Ascii);
+        processor.add_keywords_many(
+            vec!["machine learning".to_string(), "python".to_string()],
+            None,
+        );
+        let extract = |sentence: &str, max_cost: usize| -> Vec<(String, usize, usize, usize)> {
+            processor
+                .extract_keywords(sentence, Offsets::Char, MatchPolicy::Longest, max_cost)
+                .into_iter()
+                .map(|keyword| {
+                    (
+                        keyword.clean_name,
+                        keyword.start,
+                        keyword.end,
+                        keyword.distance,
+                    )
+                })
+                .collect()
+        };
+
+        let sentence: &str = "I love machin learning and pyton.";
+        assert!(extract(sentence, 0).is_empty());
+        assert_eq!(
+            extract(sentence, 1),
+            [
+                ("machine learning".to_string(), 7, 22, 1),
+                ("python".to_string(), 27, 32, 1)
+            ]
+        );
+        assert!(extract("I love machin lerning", 1).is_empty());
+        assert_eq!(
+            extract("I love machin lerning", 2),
+            [("machine learning".to_string(), 7, 21, 2)]
+        );
+
+        // The cheapest end wins over a longer span within the budget.
+        assert_eq!(
+            extract("machine learning is fun", 3),
+            [("machine learning".to_string(), 0, 16, 0)]
+        );
+
+        let sentences: Vec<String> = vec![sentence.to_string(), "pythn".to_string()];
+        let keywords: Vec<Vec<KeywordMatch>> =
+            processor.extract_keywords_many(sentences, Offsets::Char, MatchPolicy::Longest, 1);
+        assert_eq!(keywords[0].len(), 2);
+        assert_eq!(keywords[1][0].clean_name, "python");
+    }
+
+    #[test]
+    fn test_replace_keywords() {
+        let mut processor = RSKeywordProcessor::new(true, true, WordBoundaries::Ascii);
+        processor.add_keyword("NYC", Some("New York"));
+        processor.add_keyword("café", Some("coffee shop"));
+
+        assert_eq!(
+            processor.replace_keywords("I love nyc! The Café, downtown."),
+            "I love New York! The coffee shop, downtown."
+        );
+        assert_eq!(
+            processor.replace_keywords_many(vec!["Nothing here.".to_string()]),
+            vec!["Nothing here.".to_string()]
+        );
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::rsanalyzer::Analyzer;
+use crate::rserror::LeNLPError;
+#[cfg(feature = "python")]
+use crate::rssparse::Csr;
+use crate::rssparse::{normalize_row, CsrMatrix, Norm};
+use crate::rsvectorizer::rsvectorize_many;
+
+/// MurmurHash3 (x86, 32 bits) of a byte slice.
+///
+/// # Arguments
+///
+/// * `key` - The bytes to hash.
+/// * `seed` - The seed of the hash function.
+///
+/// # Returns
+///
+/// The 32 bits hash, identical to scikit-learn `murmurhash3_32` with `positive=True`.
+pub fn murmurhash3_32(key: &[u8], seed: u32) -> u32 {
+    const C1: u32 = 0xcc9e2d51;
+    const C2: u32 = 0x1b873593;
+
+    let mut hash: u32 = seed;
+    let chunks = key.chunks_exact(4);
+    let tail: &[u8] = chunks.remainder();
+
+    for chunk in chunks {
+        let mut k: u32 = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+        hash = hash
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xe6546b64);
+    }
+
+    if !tail.is_empty() {
+        let mut k: u32 = 0;
+        for (idx, &byte) in tail.iter().enumerate() {
+            k ^= (byte as u32) << (8 * idx);
+        }
+        k = k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash ^= k;
+    }
+
+    hash ^= key.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85ebca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2ae35);
+    hash ^= hash >> 16;
+    hash
+}
+
+/// Hash the token counts of a document into `n_features` columns.
+///
+/// Columns are sorted and colliding tokens are summed, as in scikit-learn.
+pub fn rshash_counts(
+    counts: &HashMap<String, usize>,
+    n_features: usize,
+    alternate_sign: bool,
+) -> Vec<(usize, f32)> {
+    let mut row: Vec<(usize, f32)> = counts
+        .iter()
+        .map(|(token, &count)| {
+            let hash: i32 = murmurhash3_32(token.as_bytes(), 0) as i32;
+            let col_idx: usize = hash.unsigned_abs() as usize % n_features;
+            let sign: f32 = if alternate_sign && hash < 0 {
+                -1.0
+            } else {
+                1.0
+            };
+            (col_idx, sign * count as f32)
+        })
+        .collect();
+
+    row.sort_unstable_by_key(|&(col_idx, _)| col_idx);
+    row.dedup_by(|current, previous| {
+        if current.0 == previous.0 {
+            previous.1 += current.1;
+            true
+        } else {
+            false
+        }
+    });
+    row.retain(|&(_, value)| value != 0.0);
+    row
+}
+
+/// Vectorize texts into a fixed number of hashed columns, without any vocabulary.
+///
+/// # Arguments
+///
+/// * `texts` - The input texts.
+/// * `n_sizes` - The size of the n-grams.
+/// * `analyzer` - One of word, char or char_wb.
+/// * `stop_words` - The stop words to filter.
+/// * `normalize` - Whether to normalize the texts.
+/// * `n_features` - The number of columns of the output matrix.
+/// * `alternate_sign` - Whether to flip the sign of half of the hashed tokens.
+/// * `norm` - Row normalization, if any.
+///
+/// # Returns
+///
+/// The hashed matrix with sorted column indices.
+#[allow(clippy::too_many_arguments)]
+pub fn rshash_vectorize_many(
+    texts: Vec<String>,
+    n_sizes: Vec<usize>,
+    analyzer: Analyzer,
+    stop_words: Option<Vec<String>>,
+    normalize: Option<bool>,
+    n_features: usize,
+    alternate_sign: bool,
+    norm: Option<Norm>,
+) -> Result<CsrMatrix<f32>, LeNLPError> {
+    if n_features == 0 {
+        return Err(LeNLPError::InvalidParameter(
+            "n_features must be strictly positive.".to_string(),
+        ));
+    }
+
+    let texts: Vec<HashMap<String, usize>> =
+        rsvectorize_many(texts, n_sizes, analyzer, stop_words, normalize, false)?;
+
+    let rows: Vec<Vec<(usize, f32)>> = texts
+        .par_iter()
+        .map(|counts: &HashMap<String, usize>| {
+            let mut row: Vec<(usize, f32)> = rshash_counts(counts, n_features, alternate_sign);
+            if let Some(norm) = norm {
+                normalize_row(&mut row, norm);
+            }
+            row
+        })
+        .collect();
+
+    Ok(CsrMatrix::from_rows(rows))
+}
+
+/// Python binding of `rshash_vectorize_many`, returning the row pointers, column
+/// indices and values as numpy arrays.
+#[cfg(feature = "python")]
+#[pyfunction(name = "rshash_vectorize_many")]
+#[pyo3(signature = (
+    texts,
+    n_sizes,
+    analyzer,
+    stop_words=None,
+    normalize=None,
+    n_features=1048576,
+    alternate_sign=true,
+    norm=Some(Norm::L2),
+))]
+#[allow(clippy::too_many_arguments)]
+fn py_rshash_vectorize_many(
+    texts: Vec<String>,
+    n_sizes: Vec<usize>,
+    analyzer: Analyzer,
+    stop_words: Option<Vec<String>>,
+    normalize: Option<bool>,
+    n_features: usize,
+    alternate_sign: bool,
+    norm: Option<Norm>,
+    py: Python<'_>,
+) -> PyResult<Csr<f32>> {
+    let matrix: CsrMatrix<f32> = py.allow_threads(|| {
+        rshash_vectorize_many(
+            texts,
+            n_sizes,
+            analyzer,
+            stop_words,
+            normalize,
+            n_features,
+            alternate_sign,
+            norm,
+        )
+    })?;
+    Ok(matrix.into_pyarrays(py))
+}
+
+/// Registers all the above functions in a Python sub-module.
+///
+/// Called from your `#[pymodule]` entry-point.
+#[cfg(feature = "python")]
+pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(py_rshash_vectorize_many, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_murmurhash3_32() {
+        // Reference values from scikit-learn murmurhash3_32.
+        assert_eq!(murmurhash3_32(b"foo", 0) as i32, -156908512);
+        assert_eq!(murmurhash3_32(b"foo", 0), 4138058784);
+        assert_eq!(murmurhash3_32(b"", 0), 0);
+    }
+
+    #[test]
+    fn test_rshash_counts() {
+        let counts: HashMap<String, usize> =
+            HashMap::from([("hello".to_string(), 2), ("world".to_string(), 1)]);
+
+        let row = rshash_counts(&counts, 1, false);
+        assert_eq!(row, vec![(0, 3.0)]);
+
+        let row = rshash_counts(&counts, 1 << 20, false);
+        assert_eq!(row.len(), 2);
+        assert!(row[0].0 < row[1].0);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::rserror::LeNLPError;
+#[cfg(feature = "python")]
+use crate::rssparse::{Bm25Idf, Bm25Variant};
+use crate::rssparse::{Bm25Params, CsrMatrix, SparseMatrixBuilder};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyModule;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+
+/// In-memory inverted index of BM25 weighted documents.
+///
+/// Each column of the fitted vocabulary has a posting list of (document, weight)
+/// pairs sorted by document. Queries are weighted with the same parameters as the
+/// documents, so the score of a document is the dot product of its row and the
+/// query row, as in `matrix @ query.T`, but only documents sharing a term with the
+/// query are visited.
+///
+/// The index owns a copy of the builder, vocabulary and statistics included, so
+/// that refitting the builder afterwards cannot make queries disagree with the
+/// indexed weights. The posting lists hold one entry per non-zero weight of the
+/// indexed documents, so memory grows with the documents as well as the vocabulary.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python", pyclass(module = "lenlp.sparse.bm25_vectorizer"))]
+pub struct InvertedIndex {
+    builder: SparseMatrixBuilder,
+    postings: Vec<Vec<(usize, f32)>>,
+    n_documents: usize,
+    params: Bm25Params,
+}
+
+impl InvertedIndex {
+    /// Index the documents with the vocabulary and statistics of a fitted builder,
+    /// which is cloned into the index.
+    pub fn new(
+        builder: &SparseMatrixBuilder,
+        documents: Vec<String>,
+        params: Bm25Params,
+    ) -> Result<Self, LeNLPError> {
+        let matrix: CsrMatrix<f32> = builder.transform_bm25(documents, params)?;
+
+        let mut postings: Vec<Vec<(usize, f32)>> = vec![Vec::new(); builder.num_cols()];
+        for (doc_idx, bounds) in matrix.indptr.windows(2).enumerate() {
+            for idx in bounds[0] as usize..bounds[1] as usize {
+                postings[matrix.indices[idx] as usize].push((doc_idx, matrix.data[idx]));
+            }
+        }
+
+        Ok(Self {
+            builder: builder.clone(),
+            postings,
+            n_documents: matrix.n_rows(),
+            params,
+        })
+    }
+
+    /// Return the k best (document, score) pairs of each query, by decreasing score.
+    ///
+    /// Ties are broken by increasing document index and documents sharing no term
+    /// with the query are never returned. Queries are scored in parallel, each thread
+    /// reusing its own score buffers.
+    pub fn search(
+        &self,
+        queries: Vec<String>,
+        k: usize,
+    ) -> Result<Vec<Vec<(usize, f32)>>, LeNLPError> {
+        let queries: CsrMatrix<f32> = self.builder.transform_bm25(queries, self.params)?;
+
+        Ok(queries
+            .indptr
+            .par_windows(2)
+            .map_init(
+                || (vec![0.0; self.n_documents], vec![false; self.n_documents]),
+                |(scores, visited), bounds: &[i64]| {
+                    let (start, end) = (bounds[0] as usize, bounds[1] as usize);
+                    self._search(
+                        &queries.indices[start..end],
+                        &queries.data[start..end],
+                        k,
+                        scores,
+                        visited,
+                    )
+                },
+            )
+            .collect())
+    }
+
+    /// Number of indexed documents.
+    pub fn n_documents(&self) -> usize {
+        self.n_documents
+    }
+
+    /// Documents containing the term of a column and their weights.
+    pub fn postings(&self, col_idx: usize) -> &[(usize, f32)] {
+        &self.postings[col_idx]
+    }
+
+    /// Score a query with zeroed `scores` and `visited` buffers of one entry per
+    /// document, which are left zeroed for the next query.
+    fn _search(
+        &self,
+        columns: &[i64],
+        weights: &[f32],
+        k: usize,
+        scores: &mut [f32],
+        visited: &mut [bool],
+    ) -> Vec<(usize, f32)> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // Accumulate the scores of the documents found in the posting lists only.
+        let mut candidates: Vec<usize> = Vec::new();
+        for (&col_idx, &weight) in columns.iter().zip(weights.iter()) {
+            for &(doc_idx, value) in &self.postings[col_idx as usize] {
+                if !visited[doc_idx] {
+                    visited[doc_idx] = true;
+                    candidates.push(doc_idx);
+                }
+                scores[doc_idx] += value * weight;
+            }
+        }
+
+        let mut results: Vec<(usize, f32)> = Vec::with_capacity(candidates.len());
+        for doc_idx in candidates {
+            if scores[doc_idx] > 0.0 {
+                results.push((doc_idx, scores[doc_idx]));
+            }
+            scores[doc_idx] = 0.0;
+            visited[doc_idx] = false;
+        }
+
+        top_k(results, k)
+    }
+}
+
+/// Keep the k best (index, score) pairs sorted by decreasing score, ties are broken
+/// by increasing index.
+pub(crate) fn top_k(mut results: Vec<(usize, f32)>, k: usize) -> Vec<(usize, f32)> {
+    let by_score = |a: &(usize, f32), b: &(usize, f32)| -> Ordering {
+        b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0))
+    };
+    if k == 0 {
+        return Vec::new();
+    }
+    if results.len() > k {
+        results.select_nth_unstable_by(k - 1, by_score);
+        results.truncate(k);
+    }
+    results.sort_unstable_by(by_score);
+    results
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl InvertedIndex {
+    #[new]
+    #[pyo3(signature = (builder, documents, k1=1.5, b=0.75, epsilon=0.0, variant="okapi", delta=None, idf=Bm25Idf::Lucene))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        builder: PyRef<'_, SparseMatrixBuilder>,
+        documents: Vec<String>,
+        k1: f32,
+        b: f32,
+        epsilon: f32,
+        variant: &str,
+        delta: Option<f32>,
+        idf: Bm25Idf,
+    ) -> PyResult<Self> {
+        let params: Bm25Params = Bm25Params {
+            k1,
+            b,
+            epsilon,
+            variant: Bm25Variant::new(variant, delta)?,
+            idf,
+        };
+        Ok(Self::new(&builder, documents, params)?)
+    }
+
+    #[pyo3(name = "search", signature = (queries, k=10))]
+    fn py_search(
+        &self,
+        queries: Vec<String>,
+        k: usize,
+        py: Python<'_>,
+    ) -> PyResult<Vec<Vec<(usize, f32)>>> {
+        Ok(py.allow_threads(|| self.search(queries, k))?)
+    }
+
+    pub fn get_n_documents(&self) -> usize {
+        self.n_documents
+    }
+
+    fn __len__(&self) -> usize {
+        self.n_documents
+    }
+}
+
+/// Registers all the above functions in a Python sub-module.
+///
+/// Called from your `#[pymodule]` entry-point.
+#[cfg(feature = "python")]
+pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<InvertedIndex>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rssparse::{Bm25Idf, Bm25Variant, SparseMatrixBuilderOptions};
+
+    #[test]
+    fn test_search_matches_matrix_product() {
+        let documents: Vec<String> = [
+            "the cat sat on the mat",
+            "the dog sat on the log",
+            "cats and dogs",
+            "a cat and a dog and a cat",
+            "nothing to see here",
+        ]
+        .iter()
+        .map(|text| text.to_string())
+        .collect();
+        let queries: Vec<String> =
+            vec!["cat dog".to_string(), "sat".to_string(), "bird".to_string()];
+
+        let mut builder = SparseMatrixBuilder::new(SparseMatrixBuilderOptions {
+            normalize: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+        builder.fit(documents.clone()).unwrap();
+
+        let params = Bm25Params {
+            variant: Bm25Variant::Plus { delta: 1.0 },
+            idf: Bm25Idf::Lucene,
+            ..Default::default()
+        };
+        let index = InvertedIndex::new(&builder, documents.clone(), params).unwrap();
+        let results: Vec<Vec<(usize, f32)>> = index.search(queries.clone(), 3).unwrap();
+
+        // Brute force ranking of matrix @ query.T.
+        let dense = |matrix: &CsrMatrix<f32>, row: usize| -> Vec<f32> {
+            let mut values: Vec<f32> = vec![0.0; builder.num_cols()];
+            for idx in matrix.indptr[row] as usize..matrix.indptr[row + 1] as usize {
+                values[matrix.indices[idx] as usize] = matrix.data[idx];
+            }
+            values
+        };
+        let matrix = builder.transform_bm25(documents, params).unwrap();
+        let query_matrix = builder.transform_bm25(queries.clone(), params).unwrap();
+        for (query_idx, result) in results.iter().enumerate() {
+            let query: Vec<f32> = dense(&query_matrix, query_idx);
+            let mut expected: Vec<(usize, f32)> = (0..index.n_documents())
+                .map(|doc_idx| {
+                    let doc: Vec<f32> = dense(&matrix, doc_idx);
+                    (doc_idx, doc.iter().zip(&query).map(|(a, b)| a * b).sum())
+                })
+                .filter(|&(_, score)| score > 0.0)
+                .collect();
+            expected.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            expected.truncate(3);
+
+            assert_eq!(result.len(), expected.len());
+            for (&(doc_idx, score), &(expected_idx, expected_score)) in
+                result.iter().zip(expected.iter())
+            {
+                assert_eq!(doc_idx, expected_idx);
+                assert!((score - expected_score).abs() < 1e-6);
+            }
+        }
+
+        assert_eq!(results[1].len(), 2);
+        assert!(results[2].is_empty());
+
+        // Buffers reused across queries of a thread do not leak scores.
+        let repeated: Vec<Vec<(usize, f32)>> = index
+            .search(queries.iter().cycle().take(300).cloned().collect(), 3)
+            .unwrap();
+        for chunk in repeated.chunks(results.len()) {
+            assert_eq!(chunk, results.as_slice());
+        }
+        assert!(index.search(vec!["cat".to_string()], 0).unwrap()[0].is_empty());
+    }
+}
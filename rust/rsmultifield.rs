@@ -0,0 +1,711 @@
+use crate::rsanalyzer::{validate_n_sizes, Analyzer};
+use crate::rserror::LeNLPError;
+use crate::rsserialization::{decode_multi_field, encode_multi_field, to_json, MultiFieldState};
+#[cfg(feature = "python")]
+use crate::rssparse::Csr;
+use crate::rssparse::{normalize_row, Bm25Idf, ColumnOrder, CsrMatrix, Norm, TermCounts};
+use crate::rsvectorizer::rsvectorize_many;
+#[cfg(feature = "python")]
+use numpy::PyArray1;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyDict, PyModule};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Analyzer configuration, boost and length normalization of one document field.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "python", derive(FromPyObject), pyo3(from_item_all))]
+pub struct FieldConfig {
+    pub name: String,
+    pub analyzer: Analyzer,
+    pub n_sizes: Vec<usize>,
+    pub stop_words: Option<Vec<String>>,
+    pub normalize: Option<bool>,
+    /// Weight of the term frequencies of the field.
+    pub boost: f32,
+    /// Impact of the field length normalization, between 0 and 1.
+    pub b: f32,
+}
+
+impl FieldConfig {
+    fn validate(&self) -> Result<(), LeNLPError> {
+        validate_n_sizes(&self.n_sizes)?;
+        if !(self.boost.is_finite() && self.boost >= 0.0) {
+            return Err(LeNLPError::InvalidParameter(format!(
+                "boost of field '{}' must be a non-negative number, got {}.",
+                self.name, self.boost
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.b) {
+            return Err(LeNLPError::InvalidParameter(format!(
+                "b of field '{}' must be between 0 and 1, got {}.",
+                self.name, self.b
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whether fields share their columns or each field has its own prefixed columns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldVocabulary {
+    /// A term found in several fields maps to a single column.
+    #[default]
+    Shared,
+    /// Terms are prefixed by their field name, as in `title:hello`.
+    Prefixed,
+}
+
+impl FromStr for FieldVocabulary {
+    type Err = LeNLPError;
+
+    fn from_str(vocabulary: &str) -> Result<Self, Self::Err> {
+        match vocabulary {
+            "shared" => Ok(FieldVocabulary::Shared),
+            "prefixed" => Ok(FieldVocabulary::Prefixed),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid field vocabulary '{}', expected 'shared' or 'prefixed'.",
+                vocabulary
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for FieldVocabulary {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<FieldVocabulary>()?)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Multi-field matrix builder
+// ---------------------------------------------------------------------------
+
+/// Builds BM25F weighted matrices of documents made of several text fields.
+///
+/// Term frequencies of each field are length-normalized with the field `b` and
+/// average length, weighted by the field boost and summed before saturation, so a
+/// term repeated across fields saturates once.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python", pyclass(module = "lenlp.sparse.bm25f_vectorizer"))]
+pub struct MultiFieldMatrixBuilder {
+    fields: Vec<FieldConfig>,
+    field_vocabulary: FieldVocabulary,
+    column_order: ColumnOrder,
+    vocab: HashMap<String, usize>,
+    /// Reverse index of `vocab`, the term of each column.
+    terms: Vec<String>,
+    num_cols: usize,
+    /// Number of fitted documents containing each term in any field.
+    document_frequencies: Vec<usize>,
+    n_documents: usize,
+    /// Average number of tokens of each field over the fitted documents.
+    average_field_lens: Vec<f32>,
+}
+
+impl MultiFieldMatrixBuilder {
+    pub fn new(
+        fields: Vec<FieldConfig>,
+        field_vocabulary: FieldVocabulary,
+        column_order: ColumnOrder,
+    ) -> Result<Self, LeNLPError> {
+        if fields.is_empty() {
+            return Err(LeNLPError::InvalidParameter(
+                "At least one field is required.".to_string(),
+            ));
+        }
+        let mut names: HashSet<&str> = HashSet::new();
+        for field in &fields {
+            field.validate()?;
+            if !names.insert(&field.name) {
+                return Err(LeNLPError::InvalidParameter(format!(
+                    "Duplicate field '{}'.",
+                    field.name
+                )));
+            }
+        }
+
+        Ok(Self {
+            average_field_lens: vec![0.0; fields.len()],
+            fields,
+            field_vocabulary,
+            column_order,
+            vocab: HashMap::new(),
+            terms: Vec::new(),
+            num_cols: 0,
+            document_frequencies: Vec::new(),
+            n_documents: 0,
+        })
+    }
+
+    /// Build the vocabulary and the field statistics.
+    pub fn fit(&mut self, documents: Vec<HashMap<String, String>>) -> Result<(), LeNLPError> {
+        let fields: Vec<Vec<HashMap<String, usize>>> = self._vectorize(documents)?;
+
+        self._fit(&fields);
+        Ok(())
+    }
+
+    /// Build the vocabulary and return the BM25F weighted matrix.
+    pub fn fit_transform_bm25f(
+        &mut self,
+        documents: Vec<HashMap<String, String>>,
+        k1: f32,
+        idf: Bm25Idf,
+    ) -> Result<CsrMatrix<f32>, LeNLPError> {
+        let fields: Vec<Vec<HashMap<String, usize>>> = self._vectorize(documents)?;
+
+        self._fit(&fields);
+
+        Ok(self._transform_bm25f(&fields, k1, idf))
+    }
+
+    /// Return the BM25F weighted matrix using the fitted statistics.
+    pub fn transform_bm25f(
+        &self,
+        documents: Vec<HashMap<String, String>>,
+        k1: f32,
+        idf: Bm25Idf,
+    ) -> Result<CsrMatrix<f32>, LeNLPError> {
+        let fields: Vec<Vec<HashMap<String, usize>>> = self._vectorize(documents)?;
+
+        Ok(self._transform_bm25f(&fields, k1, idf))
+    }
+
+    pub fn fields(&self) -> &[FieldConfig] {
+        &self.fields
+    }
+
+    pub fn vocab(&self) -> &HashMap<String, usize> {
+        &self.vocab
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn document_frequencies(&self) -> &[usize] {
+        &self.document_frequencies
+    }
+
+    pub fn n_documents(&self) -> usize {
+        self.n_documents
+    }
+
+    /// Average number of tokens of each field, in the order of the field configs.
+    pub fn average_field_lens(&self) -> &[f32] {
+        &self.average_field_lens
+    }
+
+    /// Terms ordered by column index.
+    pub fn feature_names_out(&self) -> &[String] {
+        &self.terms
+    }
+
+    // ---------------------------------------------------------------------
+    // Serialization
+    // ---------------------------------------------------------------------
+
+    /// Serialize the fitted builder in the versioned LeNLP format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, LeNLPError> {
+        encode_multi_field(&self.to_state())
+    }
+
+    /// Restore a builder serialized by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LeNLPError> {
+        Self::from_state(decode_multi_field(bytes)?)
+    }
+
+    /// Write the fitted builder to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), LeNLPError> {
+        Ok(fs::write(path, self.to_bytes()?)?)
+    }
+
+    /// Read a builder written by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LeNLPError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Export the field configurations, vocabulary and statistics as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, LeNLPError> {
+        to_json(&self.to_state())
+    }
+
+    fn to_state(&self) -> MultiFieldState {
+        MultiFieldState {
+            fields: self.fields.clone(),
+            field_vocabulary: self.field_vocabulary,
+            column_order: self.column_order,
+            vocabulary: self.terms.clone(),
+            document_frequencies: self.document_frequencies.clone(),
+            n_documents: self.n_documents,
+            average_field_lens: self.average_field_lens.clone(),
+        }
+    }
+
+    fn from_state(state: MultiFieldState) -> Result<Self, LeNLPError> {
+        if state.document_frequencies.len() != state.vocabulary.len()
+            || state.average_field_lens.len() != state.fields.len()
+        {
+            return Err(LeNLPError::Serialization(
+                "field statistics are not aligned with the vocabulary.".to_string(),
+            ));
+        }
+
+        let mut builder: MultiFieldMatrixBuilder =
+            Self::new(state.fields, state.field_vocabulary, state.column_order)?;
+        builder.vocab = state
+            .vocabulary
+            .iter()
+            .enumerate()
+            .map(|(col_idx, term)| (term.clone(), col_idx))
+            .collect();
+        if builder.vocab.len() != state.vocabulary.len() {
+            return Err(LeNLPError::Serialization(
+                "duplicate terms in the vocabulary.".to_string(),
+            ));
+        }
+        builder.num_cols = state.vocabulary.len();
+        builder.terms = state.vocabulary;
+        builder.document_frequencies = state.document_frequencies;
+        builder.n_documents = state.n_documents;
+        builder.average_field_lens = state.average_field_lens;
+        Ok(builder)
+    }
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl MultiFieldMatrixBuilder {
+    #[new]
+    #[pyo3(signature = (fields, field_vocabulary=FieldVocabulary::Shared, column_order=ColumnOrder::FirstOccurrence))]
+    fn py_new(
+        fields: Vec<FieldConfig>,
+        field_vocabulary: FieldVocabulary,
+        column_order: ColumnOrder,
+    ) -> PyResult<Self> {
+        Ok(Self::new(fields, field_vocabulary, column_order)?)
+    }
+
+    #[pyo3(name = "fit")]
+    fn py_fit(&mut self, documents: Vec<HashMap<String, String>>) -> PyResult<()> {
+        Ok(self.fit(documents)?)
+    }
+
+    #[pyo3(name = "fit_transform_bm25f", signature = (documents, k1=1.2, idf=Bm25Idf::Lucene))]
+    fn py_fit_transform_bm25f(
+        &mut self,
+        documents: Vec<HashMap<String, String>>,
+        k1: f32,
+        idf: Bm25Idf,
+        py: Python<'_>,
+    ) -> PyResult<Csr<f32>> {
+        Ok(self
+            .fit_transform_bm25f(documents, k1, idf)?
+            .into_pyarrays(py))
+    }
+
+    #[pyo3(name = "transform_bm25f", signature = (documents, k1=1.2, idf=Bm25Idf::Lucene))]
+    fn py_transform_bm25f(
+        &self,
+        documents: Vec<HashMap<String, String>>,
+        k1: f32,
+        idf: Bm25Idf,
+        py: Python<'_>,
+    ) -> PyResult<Csr<f32>> {
+        Ok(self.transform_bm25f(documents, k1, idf)?.into_pyarrays(py))
+    }
+
+    /// Vocabulary as a dict whose keys follow the column order.
+    pub fn get_vocab<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let vocab: Bound<'py, PyDict> = PyDict::new(py);
+        for (col_idx, term) in self.terms.iter().enumerate() {
+            vocab.set_item(term, col_idx)?;
+        }
+        Ok(vocab)
+    }
+
+    pub fn get_num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn get_document_frequencies(&self, py: Python<'_>) -> Py<PyArray1<usize>> {
+        PyArray1::from_slice(py, &self.document_frequencies).into()
+    }
+
+    pub fn get_n_documents(&self) -> usize {
+        self.n_documents
+    }
+
+    /// Average number of tokens of each field, keyed by field name.
+    pub fn get_average_field_lens(&self) -> HashMap<String, f32> {
+        self.fields
+            .iter()
+            .zip(self.average_field_lens.iter())
+            .map(|(field, &average_len)| (field.name.clone(), average_len))
+            .collect()
+    }
+
+    pub fn get_feature_names_out(&self) -> Vec<String> {
+        self.terms.clone()
+    }
+
+    // ---------------------------------------------------------------------
+    // Persistence
+    // ---------------------------------------------------------------------
+
+    #[pyo3(name = "save")]
+    fn py_save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.save(path)?)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "load")]
+    fn py_load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::load(path)?)
+    }
+
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> PyResult<String> {
+        Ok(self.to_json()?)
+    }
+
+    // ---------------------------------------------------------------------
+    // Pickle support
+    // ---------------------------------------------------------------------
+
+    pub fn __setstate__(&mut self, state: &Bound<'_, PyBytes>) -> PyResult<()> {
+        *self = Self::from_bytes(state.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.to_bytes()?))
+    }
+
+    /// Field configurations as dicts, the constructor argument of the pickled builder.
+    pub fn __getnewargs__<'py>(&self, py: Python<'py>) -> PyResult<(Vec<Bound<'py, PyDict>>,)> {
+        let fields: Vec<Bound<'py, PyDict>> = self
+            .fields
+            .iter()
+            .map(|field: &FieldConfig| {
+                let config: Bound<'py, PyDict> = PyDict::new(py);
+                config.set_item("name", &field.name)?;
+                config.set_item("analyzer", field.analyzer.as_str())?;
+                config.set_item("n_sizes", &field.n_sizes)?;
+                config.set_item("stop_words", &field.stop_words)?;
+                config.set_item("normalize", field.normalize)?;
+                config.set_item("boost", field.boost)?;
+                config.set_item("b", field.b)?;
+                Ok(config)
+            })
+            .collect::<PyResult<_>>()?;
+        Ok((fields,))
+    }
+}
+
+impl MultiFieldMatrixBuilder {
+    /// Count the tokens of every field of the documents, indexed by field then
+    /// document. Missing fields are empty.
+    fn _vectorize(
+        &self,
+        documents: Vec<HashMap<String, String>>,
+    ) -> Result<Vec<Vec<HashMap<String, usize>>>, LeNLPError> {
+        for document in &documents {
+            if let Some(name) = document
+                .keys()
+                .find(|name| !self.fields.iter().any(|field| &field.name == *name))
+            {
+                return Err(LeNLPError::InvalidParameter(format!(
+                    "Unknown field '{}'.",
+                    name
+                )));
+            }
+        }
+
+        self.fields
+            .iter()
+            .map(|field: &FieldConfig| {
+                let texts: Vec<String> = documents
+                    .iter()
+                    .map(|document| document.get(&field.name).cloned().unwrap_or_default())
+                    .collect();
+                rsvectorize_many(
+                    texts,
+                    field.n_sizes.clone(),
+                    field.analyzer,
+                    field.stop_words.clone(),
+                    field.normalize,
+                    false,
+                )
+            })
+            .collect()
+    }
+
+    /// Vocabulary term of a token found in a field.
+    fn _column_term(&self, field_idx: usize, token: &str) -> String {
+        match self.field_vocabulary {
+            FieldVocabulary::Shared => token.to_string(),
+            FieldVocabulary::Prefixed => format!("{}:{}", self.fields[field_idx].name, token),
+        }
+    }
+
+    fn _fit(&mut self, fields: &[Vec<HashMap<String, usize>>]) {
+        let n_documents: usize = fields[0].len();
+
+        // Merge the fields of each document so that document frequencies count a
+        // document once whatever the number of fields containing the term.
+        let documents: Vec<HashMap<String, usize>> = (0..n_documents)
+            .into_par_iter()
+            .map(|doc_idx: usize| {
+                let mut document: HashMap<String, usize> = HashMap::new();
+                for (field_idx, field) in fields.iter().enumerate() {
+                    for (token, &count) in field[doc_idx].iter() {
+                        *document
+                            .entry(self._column_term(field_idx, token))
+                            .or_insert(0) += count;
+                    }
+                }
+                document
+            })
+            .collect();
+
+        let counts: TermCounts = TermCounts::collect(&documents);
+        let mut term_indices: Vec<usize> = (0..counts.terms.len()).collect();
+        self.column_order.sort(&mut term_indices, &counts);
+
+        self.terms = term_indices
+            .iter()
+            .map(|&term_idx| counts.terms[term_idx].clone())
+            .collect();
+        self.document_frequencies = term_indices
+            .iter()
+            .map(|&term_idx| counts.document_frequencies[term_idx])
+            .collect();
+        self.vocab = self
+            .terms
+            .iter()
+            .enumerate()
+            .map(|(col_idx, term)| (term.clone(), col_idx))
+            .collect();
+        self.num_cols = self.terms.len();
+        self.n_documents = counts.n_documents;
+        self.average_field_lens = fields
+            .iter()
+            .map(|field| {
+                let n_tokens: usize = field.iter().map(|doc| doc.values().sum::<usize>()).sum();
+                if n_documents > 0 {
+                    n_tokens as f32 / n_documents as f32
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+    }
+
+    /// Compute the BM25F weights of each document, normalized by row.
+    fn _transform_bm25f(
+        &self,
+        fields: &[Vec<HashMap<String, usize>>],
+        k1: f32,
+        idf: Bm25Idf,
+    ) -> CsrMatrix<f32> {
+        let n_documents: f32 = self.n_documents as f32;
+        let idf: Vec<f32> = self
+            .document_frequencies
+            .iter()
+            .map(|&df| idf.weight(n_documents, df as f32))
+            .collect();
+
+        let rows: Vec<Vec<(usize, f32)>> = (0..fields[0].len())
+            .into_par_iter()
+            .map(|doc_idx: usize| {
+                let mut pseudo_frequencies: HashMap<usize, f32> = HashMap::new();
+                for (field_idx, field) in fields.iter().enumerate() {
+                    let config: &FieldConfig = &self.fields[field_idx];
+                    let columns: Vec<(usize, usize)> = field[doc_idx]
+                        .iter()
+                        .filter_map(|(token, &count)| {
+                            self.vocab
+                                .get(&self._column_term(field_idx, token))
+                                .map(|&col_idx| (col_idx, count))
+                        })
+                        .collect();
+
+                    let len: usize = columns.iter().map(|&(_, count)| count).sum();
+                    let average_len: f32 = self.average_field_lens[field_idx];
+                    let relative_len: f32 = if average_len > 0.0 {
+                        len as f32 / average_len
+                    } else {
+                        0.0
+                    };
+                    let length_norm: f32 = 1.0 - config.b + config.b * relative_len;
+
+                    for (col_idx, count) in columns {
+                        let tf: f32 = if length_norm > 0.0 {
+                            count as f32 / length_norm
+                        } else {
+                            count as f32
+                        };
+                        *pseudo_frequencies.entry(col_idx).or_insert(0.0) += config.boost * tf;
+                    }
+                }
+
+                let mut row: Vec<(usize, f32)> = pseudo_frequencies
+                    .into_iter()
+                    .map(|(col_idx, tf)| (col_idx, (tf * (k1 + 1.0)) / (tf + k1) * idf[col_idx]))
+                    .collect();
+                row.sort_unstable_by_key(|&(col_idx, _)| col_idx);
+                normalize_row(&mut row, Norm::L2);
+                row
+            })
+            .collect();
+
+        CsrMatrix::from_rows(rows)
+    }
+}
+
+/// Registers all the above functions in a Python sub-module.
+///
+/// Called from your `#[pymodule]` entry-point.
+#[cfg(feature = "python")]
+pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<MultiFieldMatrixBuilder>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, boost: f32, b: f32) -> FieldConfig {
+        FieldConfig {
+            name: name.to_string(),
+            analyzer: Analyzer::Word,
+            n_sizes: vec![1],
+            stop_words: None,
+            normalize: Some(true),
+            boost,
+            b,
+        }
+    }
+
+    fn document(fields: &[(&str, &str)]) -> HashMap<String, String> {
+        fields
+            .iter()
+            .map(|&(name, text)| (name.to_string(), text.to_string()))
+            .collect()
+    }
+
+    fn cell(matrix: &CsrMatrix<f32>, row: usize, col: usize) -> f32 {
+        let start: usize = matrix.indptr[row] as usize;
+        let end: usize = matrix.indptr[row + 1] as usize;
+        (start..end)
+            .find(|&idx| matrix.indices[idx] as usize == col)
+            .map_or(0.0, |idx| matrix.data[idx])
+    }
+
+    #[test]
+    fn test_bm25f() {
+        let documents = vec![
+            document(&[("title", "rust"), ("body", "fast python bindings")]),
+            document(&[("title", "python"), ("body", "rust")]),
+            document(&[("body", "nothing here")]),
+        ];
+
+        let mut builder = MultiFieldMatrixBuilder::new(
+            vec![field("title", 3.0, 0.5), field("body", 1.0, 0.75)],
+            FieldVocabulary::Shared,
+            ColumnOrder::FirstOccurrence,
+        )
+        .unwrap();
+        let matrix: CsrMatrix<f32> = builder
+            .fit_transform_bm25f(documents.clone(), 1.2, Bm25Idf::Lucene)
+            .unwrap();
+
+        // "rust" is found in the title and the body of two documents.
+        assert_eq!(builder.num_cols(), 6);
+        assert_eq!(builder.document_frequencies()[builder.vocab()["rust"]], 2);
+        assert_eq!(builder.average_field_lens(), [2.0 / 3.0, 2.0]);
+
+        // The boosted title outweighs the body.
+        let (rust, python) = (builder.vocab()["rust"], builder.vocab()["python"]);
+        assert!(cell(&matrix, 0, rust) > cell(&matrix, 0, python));
+        assert!(cell(&matrix, 1, python) > cell(&matrix, 1, rust));
+
+        let mut builder = MultiFieldMatrixBuilder::new(
+            vec![field("title", 3.0, 0.5), field("body", 1.0, 0.75)],
+            "prefixed".parse().unwrap(),
+            ColumnOrder::Lexicographic,
+        )
+        .unwrap();
+        builder.fit(documents).unwrap();
+        assert_eq!(
+            &builder.feature_names_out()[..3],
+            ["body:bindings", "body:fast", "body:here"]
+        );
+        assert_eq!(
+            builder.document_frequencies()[builder.vocab()["title:rust"]],
+            1
+        );
+
+        assert!(builder
+            .transform_bm25f(vec![document(&[("tags", "rust")])], 1.2, Bm25Idf::Lucene)
+            .is_err());
+        assert!(MultiFieldMatrixBuilder::new(
+            vec![field("title", 1.0, 0.5), field("title", 1.0, 0.5)],
+            FieldVocabulary::Shared,
+            ColumnOrder::FirstOccurrence,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let documents = vec![
+            document(&[("title", "rust"), ("body", "fast python bindings")]),
+            document(&[("title", "python"), ("body", "rust")]),
+        ];
+        let mut builder = MultiFieldMatrixBuilder::new(
+            vec![field("title", 3.0, 0.5), field("body", 1.0, 0.75)],
+            FieldVocabulary::Prefixed,
+            ColumnOrder::Frequency,
+        )
+        .unwrap();
+        let matrix: CsrMatrix<f32> = builder
+            .fit_transform_bm25f(documents.clone(), 1.2, Bm25Idf::Lucene)
+            .unwrap();
+
+        let bytes: Vec<u8> = builder.to_bytes().unwrap();
+        assert_eq!(&bytes[..8], b"LNLF\x01\x00\x00\x00");
+        let restored = MultiFieldMatrixBuilder::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.fields(), builder.fields());
+        assert_eq!(restored.feature_names_out(), builder.feature_names_out());
+        assert_eq!(restored.vocab(), builder.vocab());
+        assert_eq!(restored.average_field_lens(), builder.average_field_lens());
+        assert_eq!(
+            restored
+                .transform_bm25f(documents, 1.2, Bm25Idf::Lucene)
+                .unwrap(),
+            matrix
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&builder.to_json().unwrap()).unwrap();
+        assert_eq!(json["field_vocabulary"], "prefixed");
+        assert_eq!(json["fields"][0]["name"], "title");
+
+        // Files of a single-field builder are rejected.
+        let single = crate::rssparse::SparseMatrixBuilder::new(Default::default()).unwrap();
+        assert!(MultiFieldMatrixBuilder::from_bytes(&single.to_bytes().unwrap()).is_err());
+    }
+}
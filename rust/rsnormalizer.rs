@@ -1,4 +1,6 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::wrap_pyfunction;
 use rayon::prelude::*;
 use unidecode::unidecode;
@@ -12,7 +14,7 @@ use unidecode::unidecode;
 /// # Returns
 ///
 /// A String that holds the normalized text.
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn rsnormalize(text: &str) -> String {
     unidecode(text)
         .to_lowercase()
@@ -32,11 +34,12 @@ pub fn rsnormalize(text: &str) -> String {
 /// # Returns
 ///
 /// A vector of strings that holds the normalized texts.
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn rsnormalize_many(texts: Vec<String>) -> Vec<String> {
     texts.par_iter().map(|text| rsnormalize(text)).collect()
 }
 
+#[cfg(feature = "python")]
 pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(rsnormalize, m)?)?;
     m.add_function(wrap_pyfunction!(rsnormalize_many, m)?)?;
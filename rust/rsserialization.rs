@@ -0,0 +1,226 @@
+use bincode::{deserialize, serialize};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::rsanalyzer::Analyzer;
+use crate::rserror::LeNLPError;
+use crate::rsmultifield::{FieldConfig, FieldVocabulary};
+use crate::rssparse::{Bm25AdptTerms, ColumnOrder, DocumentFrequency};
+
+/// First bytes of the files written for a `SparseMatrixBuilder`.
+pub const MAGIC: [u8; 4] = *b"LNLP";
+
+/// First bytes of the files written for a `MultiFieldMatrixBuilder`.
+pub const MULTI_FIELD_MAGIC: [u8; 4] = *b"LNLF";
+
+/// Version of the format written by `encode` and `encode_multi_field`.
+pub const VERSION: u32 = 1;
+
+/// Fitted state of a `SparseMatrixBuilder`, as stored in the current format version.
+///
+/// The file is made of `MAGIC`, `VERSION` as a little-endian `u32` and the state
+/// encoded with bincode: little-endian fixed-size integers, `u64` length prefixes for
+/// strings and sequences and `u32` variant indices for enums.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModelState {
+    pub analyzer: Analyzer,
+    pub n_sizes: Vec<usize>,
+    pub stop_words: Option<Vec<String>>,
+    pub normalize: Option<bool>,
+    /// Whether only the presence of terms is recorded rather than their counts.
+    pub binary: bool,
+    pub smooth_idf: bool,
+    pub min_df: DocumentFrequency,
+    pub max_df: DocumentFrequency,
+    pub max_features: Option<usize>,
+    pub column_order: ColumnOrder,
+    /// Whether the vocabulary was given by the user rather than learned.
+    pub fixed_vocabulary: bool,
+    /// Terms ordered by column index.
+    pub vocabulary: Vec<String>,
+    /// Number of documents containing each term, aligned with `vocabulary`.
+    pub document_frequencies: Vec<usize>,
+    /// Number of occurrences of each term, aligned with `vocabulary`.
+    pub collection_frequencies: Vec<usize>,
+    pub n_documents: usize,
+    /// Number of in-vocabulary tokens of each fitted document.
+    pub document_lengths: Vec<usize>,
+    pub n_tokens: usize,
+    pub pruned_terms: Vec<String>,
+    /// Idf vector restored from a LeNLP 1.2.0 TfidfVectorizer pickle, if any.
+    pub legacy_idf: Option<Vec<f32>>,
+    /// Per-term parameters of BM25-adpt, if fitted.
+    pub bm25_adpt: Option<Bm25AdptTerms>,
+}
+
+/// Fitted state of a `MultiFieldMatrixBuilder`, stored after `MULTI_FIELD_MAGIC` and
+/// `VERSION` with the same encoding as `ModelState`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultiFieldState {
+    pub fields: Vec<FieldConfig>,
+    pub field_vocabulary: FieldVocabulary,
+    pub column_order: ColumnOrder,
+    /// Terms ordered by column index.
+    pub vocabulary: Vec<String>,
+    /// Number of documents containing each term in any field, aligned with `vocabulary`.
+    pub document_frequencies: Vec<usize>,
+    pub n_documents: usize,
+    /// Average number of tokens of each field, aligned with `fields`.
+    pub average_field_lens: Vec<f32>,
+}
+
+/// Layout of the pickles written by LeNLP 1.2.0, a bincode dump without header.
+#[derive(Deserialize)]
+struct LegacyState {
+    analyzer: String,
+    n_sizes: Vec<usize>,
+    stop_words: Option<Vec<String>>,
+    normalize: Option<bool>,
+    vocab: HashMap<String, usize>,
+    num_cols: usize,
+}
+
+impl TryFrom<LegacyState> for ModelState {
+    type Error = LeNLPError;
+
+    /// Legacy pickles did not store document statistics, they are left empty.
+    fn try_from(legacy: LegacyState) -> Result<Self, Self::Error> {
+        let mut vocab: Vec<(String, usize)> = legacy.vocab.into_iter().collect();
+        vocab.sort_unstable_by_key(|&(_, col_idx)| col_idx);
+        if vocab.len() != legacy.num_cols
+            || vocab
+                .iter()
+                .enumerate()
+                .any(|(idx, &(_, col_idx))| idx != col_idx)
+        {
+            return Err(LeNLPError::Serialization(
+                "legacy vocabulary columns are not contiguous.".to_string(),
+            ));
+        }
+
+        Ok(ModelState {
+            analyzer: legacy.analyzer.parse()?,
+            n_sizes: legacy.n_sizes,
+            stop_words: legacy.stop_words,
+            normalize: legacy.normalize,
+            binary: false,
+            smooth_idf: true,
+            min_df: DocumentFrequency::Absolute(1),
+            max_df: DocumentFrequency::Proportion(1.0),
+            max_features: None,
+            column_order: ColumnOrder::FirstOccurrence,
+            fixed_vocabulary: false,
+            document_frequencies: vec![0; vocab.len()],
+            collection_frequencies: vec![0; vocab.len()],
+            vocabulary: vocab.into_iter().map(|(term, _)| term).collect(),
+            n_documents: 0,
+            document_lengths: Vec::new(),
+            n_tokens: 0,
+            pruned_terms: Vec::new(),
+            legacy_idf: None,
+            bm25_adpt: None,
+        })
+    }
+}
+
+/// Write the header followed by the state.
+pub fn encode(state: &ModelState) -> Result<Vec<u8>, LeNLPError> {
+    encode_with_magic(&MAGIC, state)
+}
+
+/// Read a state written by any version of LeNLP.
+///
+/// Bytes without the magic header are read as a LeNLP 1.2.0 pickle.
+pub fn decode(bytes: &[u8]) -> Result<ModelState, LeNLPError> {
+    let Some(payload) = bytes.strip_prefix(&MAGIC) else {
+        return deserialize::<LegacyState>(bytes)?.try_into();
+    };
+    Ok(deserialize(versioned_payload(payload)?)?)
+}
+
+/// Write the multi-field header followed by the state.
+pub fn encode_multi_field(state: &MultiFieldState) -> Result<Vec<u8>, LeNLPError> {
+    encode_with_magic(&MULTI_FIELD_MAGIC, state)
+}
+
+/// Read a state written by `encode_multi_field`.
+pub fn decode_multi_field(bytes: &[u8]) -> Result<MultiFieldState, LeNLPError> {
+    let Some(payload) = bytes.strip_prefix(&MULTI_FIELD_MAGIC) else {
+        return Err(LeNLPError::Serialization(
+            "not a multi-field model.".to_string(),
+        ));
+    };
+    Ok(deserialize(versioned_payload(payload)?)?)
+}
+
+/// Human-readable export of a state.
+pub fn to_json<T: Serialize>(state: &T) -> Result<String, LeNLPError> {
+    serde_json::to_string_pretty(state)
+        .map_err(|error| LeNLPError::Serialization(error.to_string()))
+}
+
+fn encode_with_magic<T: Serialize>(magic: &[u8; 4], state: &T) -> Result<Vec<u8>, LeNLPError> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(magic.len() + 4);
+    bytes.extend_from_slice(magic);
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend(serialize(state)?);
+    Ok(bytes)
+}
+
+/// Check the format version following the magic header and return the state bytes.
+fn versioned_payload(payload: &[u8]) -> Result<&[u8], LeNLPError> {
+    if payload.len() < 4 {
+        return Err(LeNLPError::Serialization(
+            "missing format version.".to_string(),
+        ));
+    }
+    let (version, payload) = payload.split_at(4);
+    match u32::from_le_bytes([version[0], version[1], version[2], version[3]]) {
+        VERSION => Ok(payload),
+        version => Err(LeNLPError::Serialization(format!(
+            "unsupported format version {}, expected version {}.",
+            version, VERSION
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct LegacyDump {
+        analyzer: String,
+        n_sizes: Vec<usize>,
+        stop_words: Option<Vec<String>>,
+        normalize: Option<bool>,
+        vocab: HashMap<String, usize>,
+        num_cols: usize,
+    }
+
+    #[test]
+    fn test_decode_legacy_pickle() {
+        let legacy: Vec<u8> = serialize(&LegacyDump {
+            analyzer: "char_wb".to_string(),
+            n_sizes: vec![2, 3],
+            stop_words: None,
+            normalize: Some(true),
+            vocab: HashMap::from([("b".to_string(), 1), ("a".to_string(), 0)]),
+            num_cols: 2,
+        })
+        .unwrap();
+
+        let state: ModelState = decode(&legacy).unwrap();
+        assert_eq!(state.analyzer, Analyzer::CharWb);
+        assert_eq!(state.vocabulary, vec!["a", "b"]);
+        assert_eq!(state.document_frequencies, vec![0, 0]);
+
+        let encoded: Vec<u8> = encode(&state).unwrap();
+        assert_eq!(&encoded[..8], b"LNLP\x01\x00\x00\x00");
+        assert_eq!(decode(&encoded).unwrap(), state);
+
+        let mut future: Vec<u8> = encoded.clone();
+        future[4] = 2;
+        assert!(decode(&future).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::rsindex::top_k;
+#[cfg(feature = "python")]
+use crate::rssparse::Csr;
+use crate::rssparse::CsrMatrix;
+#[cfg(feature = "python")]
+use numpy::PyReadonlyArray1;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::PyModule;
+#[cfg(feature = "python")]
+use pyo3::wrap_pyfunction;
+use rayon::prelude::*;
+
+/// Row pointers, column indices and values of a sparse matrix given by Python.
+#[cfg(feature = "python")]
+type PyCsr<'py> = (
+    PyReadonlyArray1<'py, i64>,
+    PyReadonlyArray1<'py, i64>,
+    PyReadonlyArray1<'py, f32>,
+);
+
+/// Top-k cosine similarities between the rows of `a` and the rows of `b`.
+///
+/// Row `i` of the returned matrix holds the k rows of `b` most similar to row `i` of
+/// `a`, with a similarity strictly greater than `threshold`, as (row of `b`,
+/// similarity) entries sorted by column. Ties are broken in favor of the lowest rows
+/// of `b`. The product `a @ b.T` is never materialized: rows of `a` are processed in
+/// parallel, each thread reusing its own score buffers, and only visit the rows of
+/// `b` sharing one of their columns.
+pub fn cosine_top_k(
+    a: &CsrMatrix<f32>,
+    b: &CsrMatrix<f32>,
+    k: usize,
+    threshold: f32,
+) -> CsrMatrix<f32> {
+    // Columns of the L2-normalized rows of b.
+    let n_cols: usize = b
+        .indices
+        .iter()
+        .max()
+        .map_or(0, |&col_idx| col_idx as usize + 1);
+    let mut columns: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n_cols];
+    for (row_idx, bounds) in b.indptr.windows(2).enumerate() {
+        let (start, end) = (bounds[0] as usize, bounds[1] as usize);
+        let norm: f32 = l2_norm(&b.data[start..end]);
+        if norm > 0.0 {
+            for idx in start..end {
+                columns[b.indices[idx] as usize].push((row_idx, b.data[idx] / norm));
+            }
+        }
+    }
+
+    let n_rows_b: usize = b.n_rows();
+    let rows: Vec<Vec<(usize, f32)>> = a
+        .indptr
+        .par_windows(2)
+        .map_init(
+            || (vec![0.0; n_rows_b], vec![false; n_rows_b]),
+            |(scores, visited), bounds: &[i64]| {
+                let (start, end) = (bounds[0] as usize, bounds[1] as usize);
+                let norm: f32 = l2_norm(&a.data[start..end]);
+                if norm == 0.0 || k == 0 {
+                    return Vec::new();
+                }
+
+                let mut candidates: Vec<usize> = Vec::new();
+                for idx in start..end {
+                    let Some(column) = columns.get(a.indices[idx] as usize) else {
+                        continue;
+                    };
+                    let value: f32 = a.data[idx] / norm;
+                    for &(row_idx, weight) in column {
+                        if !visited[row_idx] {
+                            visited[row_idx] = true;
+                            candidates.push(row_idx);
+                        }
+                        scores[row_idx] += value * weight;
+                    }
+                }
+
+                // Reset the entries of the candidates only, leaving the buffers zeroed.
+                let mut similarities: Vec<(usize, f32)> = Vec::with_capacity(candidates.len());
+                for row_idx in candidates {
+                    if scores[row_idx] > threshold {
+                        similarities.push((row_idx, scores[row_idx]));
+                    }
+                    scores[row_idx] = 0.0;
+                    visited[row_idx] = false;
+                }
+                let mut row: Vec<(usize, f32)> = top_k(similarities, k);
+                row.sort_unstable_by_key(|&(row_idx, _)| row_idx);
+                row
+            },
+        )
+        .collect();
+
+    CsrMatrix::from_rows(rows)
+}
+
+fn l2_norm(values: &[f32]) -> f32 {
+    values.iter().map(|value| value * value).sum::<f32>().sqrt()
+}
+
+/// Top-k cosine similarities between the rows of two CSR matrices.
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (a, b, k=10, threshold=0.0))]
+pub fn rscosine_top_k(
+    a: PyCsr<'_>,
+    b: PyCsr<'_>,
+    k: usize,
+    threshold: f32,
+    py: Python<'_>,
+) -> PyResult<Csr<f32>> {
+    let into_matrix = |(indptr, indices, data): PyCsr<'_>| -> PyResult<CsrMatrix<f32>> {
+        Ok(CsrMatrix::new(
+            indptr.as_slice()?.to_vec(),
+            indices.as_slice()?.to_vec(),
+            data.as_slice()?.to_vec(),
+        )?)
+    };
+    let (a, b) = (into_matrix(a)?, into_matrix(b)?);
+
+    Ok(py
+        .allow_threads(|| cosine_top_k(&a, &b, k, threshold))
+        .into_pyarrays(py))
+}
+
+/// Registers all the above functions in a Python sub-module.
+///
+/// Called from your `#[pymodule]` entry-point.
+#[cfg(feature = "python")]
+pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(rscosine_top_k, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cosine_top_k() {
+        let a = CsrMatrix::new(vec![0, 2, 3, 3], vec![0, 1, 2], vec![1.0, 1.0, 2.0]).unwrap();
+        let b = CsrMatrix::new(
+            vec![0, 1, 3, 4, 5],
+            vec![0, 0, 1, 2, 3],
+            vec![3.0, 1.0, 1.0, 0.5, 1.0],
+        )
+        .unwrap();
+
+        let matrix: CsrMatrix<f32> = cosine_top_k(&a, &b, 2, 0.0);
+        assert_eq!(matrix.indptr, vec![0, 2, 3, 3]);
+        assert_eq!(matrix.indices, vec![0, 1, 2]);
+        let expected: [f32; 3] = [0.5_f32.sqrt(), 1.0, 1.0];
+        for (value, expected) in matrix.data.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-6);
+        }
+
+        // Only the identical row of b passes the threshold.
+        let matrix: CsrMatrix<f32> = cosine_top_k(&a, &b, 2, 0.9);
+        assert_eq!(matrix.indptr, vec![0, 1, 2, 2]);
+        assert_eq!(matrix.indices, vec![1, 2]);
+
+        let matrix: CsrMatrix<f32> = cosine_top_k(&a, &b, 1, 0.0);
+        assert_eq!(matrix.indices, vec![1, 2]);
+
+        assert!(CsrMatrix::new(vec![0, 2], vec![0], vec![1.0]).is_err());
+    }
+
+    #[test]
+    fn test_cosine_top_k_sparse_rows() {
+        // Rows of a cycle through an empty row, a row sharing no column with b, and
+        // rows matching the first and the last row of b.
+        let b = CsrMatrix::new(vec![0, 1, 2, 4], vec![0, 1, 0, 1], vec![1.0; 4]).unwrap();
+        let (mut indptr, mut indices) = (vec![0_i64], Vec::new());
+        for row_idx in 0..1000 {
+            match row_idx % 4 {
+                0 => {}
+                1 => indices.push(5),
+                2 => indices.push(0),
+                _ => indices.push(1),
+            }
+            indptr.push(indices.len() as i64);
+        }
+        let data: Vec<f32> = vec![1.0; indices.len()];
+        let a = CsrMatrix::new(indptr, indices, data).unwrap();
+
+        let matrix: CsrMatrix<f32> = cosine_top_k(&a, &b, 3, 0.0);
+        for row_idx in 0..1000 {
+            let (start, end) = (
+                matrix.indptr[row_idx] as usize,
+                matrix.indptr[row_idx + 1] as usize,
+            );
+            let expected: &[i64] = match row_idx % 4 {
+                0 | 1 => &[],
+                2 => &[0, 2],
+                _ => &[1, 2],
+            };
+            assert_eq!(&matrix.indices[start..end], expected);
+            if !expected.is_empty() {
+                assert!((matrix.data[start] - 1.0).abs() < 1e-6);
+                assert!((matrix.data[start + 1] - 0.5_f32.sqrt()).abs() < 1e-6);
+            }
+        }
+    }
+}
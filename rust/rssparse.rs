@@ -1,157 +1,819 @@
+use crate::rsanalyzer::{validate_n_sizes, Analyzer};
+use crate::rserror::LeNLPError;
+use crate::rsserialization::{decode, encode, to_json, ModelState};
 use crate::rsvectorizer::rsvectorize_many;
-use bincode::{deserialize, serialize};
-use numpy::PyArray1;
+#[cfg(feature = "python")]
+use numpy::{Element, PyArray1, PyReadonlyArray1};
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyModule}; // NEW
+#[cfg(feature = "python")]
+use pyo3::types::{PyBytes, PyDict, PyModule}; // NEW
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+#[cfg(feature = "python")]
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Row pointers, column indices and values of a sparse matrix in CSR format.
+#[cfg(feature = "python")]
+pub(crate) type Csr<T> = (Py<PyArray1<i64>>, Py<PyArray1<i64>>, Py<PyArray1<T>>);
+
+/// Constructor arguments used to rebuild a pickled builder.
+#[cfg(feature = "python")]
+type NewArgs = (Vec<usize>, String, Option<Vec<String>>, Option<bool>);
 
 // ---------------------------------------------------------------------------
 // Sparse-matrix builder
 // ---------------------------------------------------------------------------
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[pyclass(module = "lenlp.sparse.count_vectorizer")]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python", pyclass(module = "lenlp.sparse.count_vectorizer"))]
 pub struct SparseMatrixBuilder {
-    analyzer: String,
+    analyzer: Analyzer,
     n_sizes: Vec<usize>,
     stop_words: Option<Vec<String>>,
     normalize: Option<bool>,
     vocab: HashMap<String, usize>,
+    /// Reverse index of `vocab`, the term of each column.
+    terms: Vec<String>,
     num_cols: usize,
+    document_frequencies: Vec<usize>,
+    /// Number of occurrences of each term in the fitted corpus.
+    collection_frequencies: Vec<usize>,
+    n_documents: usize,
+    /// Number of in-vocabulary tokens of each fitted document.
+    document_lengths: Vec<usize>,
+    n_tokens: usize,
+    average_len: f32,
+    smooth_idf: bool,
+    idf: Vec<f32>,
+    /// Idf vector of a LeNLP 1.2.0 TfidfVectorizer pickle, used instead of the one
+    /// computed from the document frequencies until the next fit.
+    legacy_idf: Option<Vec<f32>>,
+    /// Per-term k1 and weights of BM25-adpt, fitted with `fit_bm25_adpt`.
+    bm25_adpt: Option<Bm25AdptTerms>,
+    min_df: DocumentFrequency,
+    max_df: DocumentFrequency,
+    max_features: Option<usize>,
+    pruned_terms: Vec<String>,
+    column_order: ColumnOrder,
+    /// Whether the vocabulary was given by the user and is kept unchanged by `fit`.
+    fixed_vocabulary: bool,
+    /// Whether only the presence of terms is recorded rather than their counts.
+    binary: bool,
+}
+
+/// Configuration of a `SparseMatrixBuilder`.
+///
+/// The defaults match the Python constructor: word unigrams without normalization or
+/// stop words, no pruning and columns ordered by first occurrence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SparseMatrixBuilderOptions {
+    /// Sizes of the extracted n-grams.
+    pub n_sizes: Vec<usize>,
+    pub analyzer: Analyzer,
+    pub stop_words: Option<Vec<String>>,
+    /// Whether to lowercase the texts and remove accents and punctuation.
+    pub normalize: Option<bool>,
+    /// Whether to add one to document frequencies when computing the idf.
+    pub smooth_idf: bool,
+    pub min_df: DocumentFrequency,
+    pub max_df: DocumentFrequency,
+    /// Only keep the most frequent terms across the corpus.
+    pub max_features: Option<usize>,
+    pub column_order: ColumnOrder,
+    /// Vocabulary kept unchanged by `fit`, pruning and column order are then ignored.
+    pub vocabulary: Option<Vocabulary>,
+    /// Whether only the presence of terms is recorded rather than their counts.
+    pub binary: bool,
+}
+
+impl Default for SparseMatrixBuilderOptions {
+    fn default() -> Self {
+        SparseMatrixBuilderOptions {
+            n_sizes: vec![1],
+            analyzer: Analyzer::Word,
+            stop_words: None,
+            normalize: None,
+            smooth_idf: true,
+            min_df: DocumentFrequency::Absolute(1),
+            max_df: DocumentFrequency::Proportion(1.0),
+            max_features: None,
+            column_order: ColumnOrder::FirstOccurrence,
+            vocabulary: None,
+            binary: false,
+        }
+    }
 }
 
-#[pymethods]
 impl SparseMatrixBuilder {
-    #[new]
-    pub fn new(
-        n_sizes: Vec<usize>,
-        analyzer: String,
-        stop_words: Option<Vec<String>>,
-        normalize: Option<bool>,
-    ) -> Self {
-        Self {
+    pub fn new(options: SparseMatrixBuilderOptions) -> Result<Self, LeNLPError> {
+        let SparseMatrixBuilderOptions {
+            n_sizes,
+            analyzer,
+            stop_words,
+            normalize,
+            smooth_idf,
+            min_df,
+            max_df,
+            max_features,
+            column_order,
+            vocabulary,
+            binary,
+        } = options;
+        validate_n_sizes(&n_sizes)?;
+        min_df.validate("min_df")?;
+        max_df.validate("max_df")?;
+        let mut builder: SparseMatrixBuilder = Self {
             vocab: HashMap::new(),
+            terms: Vec::new(),
             n_sizes,
             analyzer,
             stop_words,
             normalize,
             num_cols: 0,
+            document_frequencies: Vec::new(),
+            collection_frequencies: Vec::new(),
+            n_documents: 0,
+            document_lengths: Vec::new(),
+            n_tokens: 0,
+            average_len: 0.0,
+            smooth_idf,
+            idf: Vec::new(),
+            legacy_idf: None,
+            bm25_adpt: None,
+            min_df,
+            max_df,
+            max_features,
+            pruned_terms: Vec::new(),
+            column_order,
+            fixed_vocabulary: false,
+            binary,
+        };
+
+        if let Some(vocabulary) = vocabulary {
+            builder._set_vocabulary(vocabulary.into_terms()?)?;
+            builder.document_frequencies = vec![0; builder.num_cols];
+            builder.collection_frequencies = vec![0; builder.num_cols];
+            builder.fixed_vocabulary = true;
+            builder._update_statistics();
+        }
+        Ok(builder)
+    }
+
+    /// Build the vocabulary and return the count matrix.
+    pub fn fit_transform(&mut self, texts: Vec<String>) -> Result<CsrMatrix<u32>, LeNLPError> {
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
+
+        self._fit(texts.clone())?;
+
+        Ok(self._transform(texts))
+    }
+
+    pub fn fit(&mut self, texts: Vec<String>) -> Result<(), LeNLPError> {
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
+
+        self._fit(texts)
+    }
+
+    /// Build the vocabulary from an iterator of document batches.
+    ///
+    /// Each batch is vectorized in parallel and only the term statistics are kept
+    /// between batches, so peak memory is bounded by the batch size and the vocabulary.
+    /// As documents are not kept, document lengths also count the tokens of pruned or
+    /// out-of-vocabulary terms, while `fit` only counts in-vocabulary tokens. Both are
+    /// equal when no term is pruned.
+    pub fn fit_chunks<I>(&mut self, chunks: I) -> Result<(), LeNLPError>
+    where
+        I: IntoIterator<Item = Vec<String>>,
+    {
+        let mut counts: TermCounts = TermCounts::new();
+        for texts in chunks {
+            counts.update(&self._vectorize(texts)?);
         }
+
+        self._fit_counts(counts)
+    }
+
+    /// Update the vocabulary and the document statistics with a new batch of documents.
+    ///
+    /// New terms are appended after the existing columns, so matrices transformed
+    /// before the update remain column-compatible. Pruning parameters only apply to `fit`.
+    pub fn partial_fit(&mut self, texts: Vec<String>) -> Result<(), LeNLPError> {
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
+
+        self._partial_fit(texts);
+        Ok(())
+    }
+
+    /// Return the count matrix of the texts using the fitted vocabulary.
+    pub fn transform(&self, texts: Vec<String>) -> Result<CsrMatrix<u32>, LeNLPError> {
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
+
+        Ok(self._transform(texts))
     }
 
-    /// Build the vocabulary and return the CSR triplet arrays.
-    pub fn fit_transform(
+    /// Build the vocabulary and return the BM25 weighted matrix.
+    pub fn fit_transform_bm25(
         &mut self,
         texts: Vec<String>,
-        py: Python<'_>,
-    ) -> (
-        Py<PyArray1<usize>>,
-        Py<PyArray1<usize>>,
-        Py<PyArray1<usize>>,
-    ) {
-        self.vocab = HashMap::new();
-        let texts: Vec<HashMap<String, usize>> = rsvectorize_many(
-            texts,
-            self.n_sizes.clone(),
-            self.analyzer.clone(),
-            self.stop_words.clone(),
-            self.normalize,
-        );
+        params: Bm25Params,
+    ) -> Result<CsrMatrix<f32>, LeNLPError> {
+        params.variant.validate()?;
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
 
-        self._fit(texts.clone());
+        self._fit(texts.clone())?;
+        if params.variant == Bm25Variant::Adpt {
+            self.bm25_adpt = Some(self._fit_bm25_adpt(texts.clone(), params.b));
+        }
 
-        // Scipy csr_matrix are faster to build from numpy arrays.
-        let (vec1, vec2, vec3) = self._transform(texts);
-        (
-            PyArray1::from_vec_bound(py, vec1).into(),
-            PyArray1::from_vec_bound(py, vec2).into(),
-            PyArray1::from_vec_bound(py, vec3).into(),
-        )
+        Ok(self._transform_bm25(texts, params))
     }
 
-    pub fn fit(&mut self, texts: Vec<String>) {
-        self.vocab = HashMap::new();
-        let texts: Vec<HashMap<String, usize>> = rsvectorize_many(
-            texts,
-            self.n_sizes.clone(),
-            self.analyzer.clone(),
-            self.stop_words.clone(),
-            self.normalize,
-        );
+    /// Return the BM25 weighted matrix using the fitted statistics.
+    ///
+    /// BM25-adpt needs the per-term parameters fitted with the same `b` by
+    /// `fit_bm25_adpt` or `fit_transform_bm25`.
+    pub fn transform_bm25(
+        &self,
+        texts: Vec<String>,
+        params: Bm25Params,
+    ) -> Result<CsrMatrix<f32>, LeNLPError> {
+        params.variant.validate()?;
+        self._check_bm25_adpt(params.b, params.variant)?;
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
 
-        self._fit(texts);
+        Ok(self._transform_bm25(texts, params))
     }
 
-    fn _fit(&mut self, texts: Vec<HashMap<String, usize>>) {
-        let mut col_index: usize = 0;
-        for doc in &texts {
-            for token in doc.keys() {
-                if !self.vocab.contains_key(token) {
-                    self.vocab.insert(token.clone(), col_index);
-                    col_index += 1;
-                }
+    /// Build the vocabulary and fit the per-term k1 and weights of BM25-adpt with the
+    /// length normalization `b`.
+    pub fn fit_bm25_adpt(&mut self, texts: Vec<String>, b: f32) -> Result<(), LeNLPError> {
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
+
+        self._fit(texts.clone())?;
+        self.bm25_adpt = Some(self._fit_bm25_adpt(texts, b));
+        Ok(())
+    }
+
+    /// Build the vocabulary and return the tf-idf weighted matrix.
+    pub fn fit_transform_tfidf(
+        &mut self,
+        texts: Vec<String>,
+        sublinear_tf: bool,
+        use_idf: bool,
+        norm: Option<Norm>,
+    ) -> Result<CsrMatrix<f32>, LeNLPError> {
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
+
+        self._fit(texts.clone())?;
+
+        Ok(self._transform_tfidf(texts, sublinear_tf, use_idf, norm))
+    }
+
+    /// Return the tf-idf weighted matrix using the fitted idf.
+    pub fn transform_tfidf(
+        &self,
+        texts: Vec<String>,
+        sublinear_tf: bool,
+        use_idf: bool,
+        norm: Option<Norm>,
+    ) -> Result<CsrMatrix<f32>, LeNLPError> {
+        let texts: Vec<HashMap<String, usize>> = self._vectorize(texts)?;
+
+        Ok(self._transform_tfidf(texts, sublinear_tf, use_idf, norm))
+    }
+
+    // ---------------------------------------------------------------------
+    // Accessors
+    // ---------------------------------------------------------------------
+    /// Mapping from each fitted term to its column index.
+    pub fn vocab(&self) -> &HashMap<String, usize> {
+        &self.vocab
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    /// Terms removed by `min_df`, `max_df` or `max_features`, sorted alphabetically.
+    pub fn pruned_terms(&self) -> &[String] {
+        &self.pruned_terms
+    }
+
+    pub fn idf(&self) -> &[f32] {
+        &self.idf
+    }
+
+    /// Per-term k1 and weights of BM25-adpt, if fitted.
+    pub fn bm25_adpt(&self) -> Option<&Bm25AdptTerms> {
+        self.bm25_adpt.as_ref()
+    }
+
+    /// Whether the vocabulary was given to the constructor.
+    pub fn fixed_vocabulary(&self) -> bool {
+        self.fixed_vocabulary
+    }
+
+    /// Whether only the presence of terms is recorded rather than their counts.
+    pub fn binary(&self) -> bool {
+        self.binary
+    }
+
+    /// Number of fitted documents containing each term, aligned with the columns.
+    pub fn document_frequencies(&self) -> &[usize] {
+        &self.document_frequencies
+    }
+
+    /// Number of occurrences of each term in the fitted corpus, aligned with the columns.
+    pub fn collection_frequencies(&self) -> &[usize] {
+        &self.collection_frequencies
+    }
+
+    /// Number of in-vocabulary tokens of each fitted document.
+    pub fn document_lengths(&self) -> &[usize] {
+        &self.document_lengths
+    }
+
+    pub fn n_documents(&self) -> usize {
+        self.n_documents
+    }
+
+    /// Average number of in-vocabulary tokens per fitted document.
+    pub fn average_len(&self) -> f32 {
+        self.average_len
+    }
+
+    /// Terms ordered by column index.
+    pub fn feature_names_out(&self) -> &[String] {
+        &self.terms
+    }
+
+    /// Map the non-zero columns of each row of a CSR matrix back to their terms.
+    ///
+    /// # Arguments
+    ///
+    /// * `indptr` - The row pointers of the matrix.
+    /// * `indices` - The column indices of the matrix.
+    ///
+    /// # Returns
+    ///
+    /// The terms of the stored columns of each row, in storage order.
+    pub fn inverse_transform(
+        &self,
+        indptr: &[i64],
+        indices: &[i64],
+    ) -> Result<Vec<Vec<String>>, LeNLPError> {
+        let is_valid: bool = !indptr.is_empty()
+            && indptr[0] == 0
+            && indptr.windows(2).all(|bounds| bounds[0] <= bounds[1])
+            && indptr[indptr.len() - 1] as usize <= indices.len();
+        if !is_valid {
+            return Err(LeNLPError::InvalidParameter(
+                "indptr must start at 0, be non-decreasing and end within indices.".to_string(),
+            ));
+        }
+
+        indptr
+            .windows(2)
+            .map(|bounds| {
+                indices[bounds[0] as usize..bounds[1] as usize]
+                    .iter()
+                    .map(|&col_idx| {
+                        usize::try_from(col_idx)
+                            .ok()
+                            .and_then(|col_idx| self.terms.get(col_idx))
+                            .cloned()
+                            .ok_or_else(|| {
+                                LeNLPError::InvalidParameter(format!(
+                                    "Column index {} is out of the vocabulary of {} terms.",
+                                    col_idx, self.num_cols
+                                ))
+                            })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // ---------------------------------------------------------------------
+    // Serialization
+    // ---------------------------------------------------------------------
+
+    /// Serialize the fitted builder in the versioned LeNLP format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, LeNLPError> {
+        encode(&self.to_state())
+    }
+
+    /// Restore a builder serialized by any version of LeNLP.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LeNLPError> {
+        Self::from_state(decode(bytes)?)
+    }
+
+    /// Write the fitted builder to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), LeNLPError> {
+        Ok(fs::write(path, self.to_bytes()?)?)
+    }
+
+    /// Read a builder written by `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, LeNLPError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Export the configuration, vocabulary and statistics as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, LeNLPError> {
+        to_json(&self.to_state())
+    }
+
+    /// Restore the document statistics of a builder read from a LeNLP 1.2.0 pickle,
+    /// which only stored the vocabulary. Collection frequencies and document lengths
+    /// are not recoverable and stay empty.
+    pub fn set_document_statistics(
+        &mut self,
+        document_frequencies: Vec<usize>,
+        n_documents: usize,
+        n_tokens: usize,
+    ) -> Result<(), LeNLPError> {
+        if document_frequencies.len() != self.num_cols {
+            return Err(LeNLPError::InvalidParameter(format!(
+                "expected {} document frequencies, got {}.",
+                self.num_cols,
+                document_frequencies.len()
+            )));
+        }
+
+        self.document_frequencies = document_frequencies;
+        self.n_documents = n_documents;
+        self.n_tokens = n_tokens;
+        self._update_statistics();
+        Ok(())
+    }
+
+    /// Restore the idf vector of a LeNLP 1.2.0 TfidfVectorizer pickle, which did not
+    /// store document frequencies. The vector is kept until the next fit.
+    pub fn set_legacy_idf(&mut self, idf: Vec<f32>) -> Result<(), LeNLPError> {
+        if idf.len() != self.num_cols {
+            return Err(LeNLPError::InvalidParameter(format!(
+                "expected {} idf values, got {}.",
+                self.num_cols,
+                idf.len()
+            )));
+        }
+
+        self.legacy_idf = Some(idf);
+        self._update_idf();
+        Ok(())
+    }
+
+    fn to_state(&self) -> ModelState {
+        ModelState {
+            analyzer: self.analyzer,
+            n_sizes: self.n_sizes.clone(),
+            stop_words: self.stop_words.clone(),
+            normalize: self.normalize,
+            binary: self.binary,
+            smooth_idf: self.smooth_idf,
+            min_df: self.min_df,
+            max_df: self.max_df,
+            max_features: self.max_features,
+            column_order: self.column_order,
+            fixed_vocabulary: self.fixed_vocabulary,
+            vocabulary: self.terms.clone(),
+            document_frequencies: self.document_frequencies.clone(),
+            collection_frequencies: self.collection_frequencies.clone(),
+            n_documents: self.n_documents,
+            document_lengths: self.document_lengths.clone(),
+            n_tokens: self.n_tokens,
+            pruned_terms: self.pruned_terms.clone(),
+            legacy_idf: self.legacy_idf.clone(),
+            bm25_adpt: self.bm25_adpt.clone(),
+        }
+    }
+
+    fn from_state(state: ModelState) -> Result<Self, LeNLPError> {
+        let mut builder: SparseMatrixBuilder = Self::new(SparseMatrixBuilderOptions {
+            n_sizes: state.n_sizes,
+            analyzer: state.analyzer,
+            stop_words: state.stop_words,
+            normalize: state.normalize,
+            smooth_idf: state.smooth_idf,
+            min_df: state.min_df,
+            max_df: state.max_df,
+            max_features: state.max_features,
+            column_order: state.column_order,
+            vocabulary: None,
+            binary: state.binary,
+        })?;
+
+        if state.document_frequencies.len() != state.vocabulary.len()
+            || state.collection_frequencies.len() != state.vocabulary.len()
+        {
+            return Err(LeNLPError::Serialization(
+                "term frequencies are not aligned with the vocabulary.".to_string(),
+            ));
+        }
+
+        builder._set_vocabulary(state.vocabulary)?;
+        builder.fixed_vocabulary = state.fixed_vocabulary;
+        builder.document_frequencies = state.document_frequencies;
+        builder.collection_frequencies = state.collection_frequencies;
+        builder.n_documents = state.n_documents;
+        builder.document_lengths = state.document_lengths;
+        builder.n_tokens = state.n_tokens;
+        builder.pruned_terms = state.pruned_terms;
+        builder._update_statistics();
+        if let Some(idf) = state.legacy_idf {
+            builder.set_legacy_idf(idf)?;
+        }
+        if let Some(bm25_adpt) = state.bm25_adpt {
+            if bm25_adpt.k1.len() != builder.num_cols || bm25_adpt.weights.len() != builder.num_cols
+            {
+                return Err(LeNLPError::Serialization(
+                    "BM25-adpt parameters are not aligned with the vocabulary.".to_string(),
+                ));
             }
+            builder.bm25_adpt = Some(bm25_adpt);
+        }
+        Ok(builder)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Python bindings
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl SparseMatrixBuilder {
+    #[new]
+    #[pyo3(signature = (
+        n_sizes,
+        analyzer,
+        stop_words=None,
+        normalize=None,
+        smooth_idf=true,
+        min_df=DocumentFrequency::Absolute(1),
+        max_df=DocumentFrequency::Proportion(1.0),
+        max_features=None,
+        column_order=ColumnOrder::FirstOccurrence,
+        vocabulary=None,
+        binary=false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn py_new(
+        n_sizes: Vec<usize>,
+        analyzer: Analyzer,
+        stop_words: Option<Vec<String>>,
+        normalize: Option<bool>,
+        smooth_idf: bool,
+        min_df: DocumentFrequency,
+        max_df: DocumentFrequency,
+        max_features: Option<usize>,
+        column_order: ColumnOrder,
+        vocabulary: Option<Vocabulary>,
+        binary: bool,
+    ) -> PyResult<Self> {
+        Ok(Self::new(SparseMatrixBuilderOptions {
+            n_sizes,
+            analyzer,
+            stop_words,
+            normalize,
+            smooth_idf,
+            min_df,
+            max_df,
+            max_features,
+            column_order,
+            vocabulary,
+            binary,
+        })?)
+    }
+
+    /// Build the vocabulary and return the CSR arrays.
+    #[pyo3(name = "fit_transform")]
+    fn py_fit_transform(&mut self, texts: Vec<String>, py: Python<'_>) -> PyResult<PyObject> {
+        let matrix: CsrMatrix<u32> = self.fit_transform(texts)?;
+        self._counts_into_pyarrays(matrix, py)
+    }
+
+    #[pyo3(name = "fit")]
+    fn py_fit(&mut self, texts: Vec<String>) -> PyResult<()> {
+        Ok(self.fit(texts)?)
+    }
+
+    /// Build the vocabulary from an iterable of document batches.
+    #[pyo3(name = "fit_chunks")]
+    fn py_fit_chunks(&mut self, chunks: &Bound<'_, PyAny>, py: Python<'_>) -> PyResult<()> {
+        let mut counts: TermCounts = TermCounts::new();
+        for chunk in chunks.try_iter()? {
+            let texts: Vec<String> = chunk?.extract()?;
+            let texts: Vec<HashMap<String, usize>> = py.allow_threads(|| self._vectorize(texts))?;
+            counts.update(&texts);
         }
-        self.num_cols = col_index;
+
+        Ok(self._fit_counts(counts)?)
+    }
+
+    #[pyo3(name = "partial_fit")]
+    fn py_partial_fit(&mut self, texts: Vec<String>) -> PyResult<()> {
+        Ok(self.partial_fit(texts)?)
+    }
+
+    #[pyo3(name = "transform")]
+    fn py_transform(&self, texts: Vec<String>, py: Python<'_>) -> PyResult<PyObject> {
+        let matrix: CsrMatrix<u32> = self.transform(texts)?;
+        self._counts_into_pyarrays(matrix, py)
+    }
+
+    #[pyo3(
+        name = "fit_transform_bm25",
+        signature = (texts, k1=1.5, b=0.75, epsilon=0.0, variant="okapi", delta=None, idf=Bm25Idf::Lucene)
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_fit_transform_bm25(
+        &mut self,
+        texts: Vec<String>,
+        k1: f32,
+        b: f32,
+        epsilon: f32,
+        variant: &str,
+        delta: Option<f32>,
+        idf: Bm25Idf,
+        py: Python<'_>,
+    ) -> PyResult<Csr<f32>> {
+        let params: Bm25Params = Bm25Params {
+            k1,
+            b,
+            epsilon,
+            variant: Bm25Variant::new(variant, delta)?,
+            idf,
+        };
+        Ok(self.fit_transform_bm25(texts, params)?.into_pyarrays(py))
     }
 
-    pub fn transform(
+    #[pyo3(
+        name = "transform_bm25",
+        signature = (texts, k1=1.5, b=0.75, epsilon=0.0, variant="okapi", delta=None, idf=Bm25Idf::Lucene)
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn py_transform_bm25(
         &self,
         texts: Vec<String>,
+        k1: f32,
+        b: f32,
+        epsilon: f32,
+        variant: &str,
+        delta: Option<f32>,
+        idf: Bm25Idf,
         py: Python<'_>,
-    ) -> (
-        Py<PyArray1<usize>>,
-        Py<PyArray1<usize>>,
-        Py<PyArray1<usize>>,
-    ) {
-        let texts: Vec<HashMap<String, usize>> = rsvectorize_many(
-            texts,
-            self.n_sizes.clone(),
-            self.analyzer.clone(),
-            self.stop_words.clone(),
-            self.normalize,
-        );
+    ) -> PyResult<Csr<f32>> {
+        let params: Bm25Params = Bm25Params {
+            k1,
+            b,
+            epsilon,
+            variant: Bm25Variant::new(variant, delta)?,
+            idf,
+        };
+        Ok(self.transform_bm25(texts, params)?.into_pyarrays(py))
+    }
 
-        // Scipy csr_matrix are faster to build from numpy arrays.
-        let (vec1, vec2, vec3) = self._transform(texts);
-        (
-            PyArray1::from_vec_bound(py, vec1).into(),
-            PyArray1::from_vec_bound(py, vec2).into(),
-            PyArray1::from_vec_bound(py, vec3).into(),
-        )
+    #[pyo3(name = "fit_bm25_adpt", signature = (texts, b=0.75))]
+    fn py_fit_bm25_adpt(&mut self, texts: Vec<String>, b: f32) -> PyResult<()> {
+        Ok(self.fit_bm25_adpt(texts, b)?)
+    }
+
+    #[pyo3(
+        name = "fit_transform_tfidf",
+        signature = (texts, sublinear_tf=false, use_idf=true, norm=Some(Norm::L2))
+    )]
+    fn py_fit_transform_tfidf(
+        &mut self,
+        texts: Vec<String>,
+        sublinear_tf: bool,
+        use_idf: bool,
+        norm: Option<Norm>,
+        py: Python<'_>,
+    ) -> PyResult<Csr<f32>> {
+        Ok(self
+            .fit_transform_tfidf(texts, sublinear_tf, use_idf, norm)?
+            .into_pyarrays(py))
     }
 
-    fn _transform(
+    #[pyo3(
+        name = "transform_tfidf",
+        signature = (texts, sublinear_tf=false, use_idf=true, norm=Some(Norm::L2))
+    )]
+    fn py_transform_tfidf(
         &self,
-        texts: Vec<HashMap<String, usize>>,
-    ) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
-        let mut values: Vec<usize> = Vec::new();
-        let mut row_indices: Vec<usize> = Vec::new();
-        let mut column_indices: Vec<usize> = Vec::new();
+        texts: Vec<String>,
+        sublinear_tf: bool,
+        use_idf: bool,
+        norm: Option<Norm>,
+        py: Python<'_>,
+    ) -> PyResult<Csr<f32>> {
+        Ok(self
+            .transform_tfidf(texts, sublinear_tf, use_idf, norm)?
+            .into_pyarrays(py))
+    }
 
-        for (row_idx, doc) in texts.iter().enumerate() {
-            for (token, &count) in doc.iter() {
-                if let Some(&col_idx) = self.vocab.get(token) {
-                    values.push(count);
-                    row_indices.push(row_idx);
-                    column_indices.push(col_idx);
-                }
-            }
+    /// Vocabulary as a dict whose keys follow the column order.
+    pub fn get_vocab<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let vocab: Bound<'py, PyDict> = PyDict::new(py);
+        for (col_idx, term) in self.terms.iter().enumerate() {
+            vocab.set_item(term, col_idx)?;
         }
+        Ok(vocab)
+    }
+
+    pub fn get_num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn get_pruned_terms(&self) -> Vec<String> {
+        self.pruned_terms.clone()
+    }
+
+    pub fn get_idf(&self, py: Python<'_>) -> Py<PyArray1<f32>> {
+        PyArray1::from_vec(py, self.idf.clone()).into()
+    }
+
+    pub fn get_fixed_vocabulary(&self) -> bool {
+        self.fixed_vocabulary
+    }
+
+    pub fn get_binary(&self) -> bool {
+        self.binary
+    }
+
+    pub fn get_document_frequencies(&self, py: Python<'_>) -> Py<PyArray1<usize>> {
+        PyArray1::from_slice(py, &self.document_frequencies).into()
+    }
+
+    pub fn get_collection_frequencies(&self, py: Python<'_>) -> Py<PyArray1<usize>> {
+        PyArray1::from_slice(py, &self.collection_frequencies).into()
+    }
+
+    pub fn get_document_lengths(&self, py: Python<'_>) -> Py<PyArray1<usize>> {
+        PyArray1::from_slice(py, &self.document_lengths).into()
+    }
 
-        (values, row_indices, column_indices)
+    pub fn get_n_documents(&self) -> usize {
+        self.n_documents
+    }
+
+    pub fn get_average_len(&self) -> f32 {
+        self.average_len
+    }
+
+    pub fn get_feature_names_out(&self) -> Vec<String> {
+        self.terms.clone()
+    }
+
+    #[pyo3(name = "set_document_statistics")]
+    fn py_set_document_statistics(
+        &mut self,
+        document_frequencies: Vec<usize>,
+        n_documents: usize,
+        n_tokens: usize,
+    ) -> PyResult<()> {
+        Ok(self.set_document_statistics(document_frequencies, n_documents, n_tokens)?)
+    }
+
+    #[pyo3(name = "set_legacy_idf")]
+    fn py_set_legacy_idf(&mut self, idf: Vec<f32>) -> PyResult<()> {
+        Ok(self.set_legacy_idf(idf)?)
+    }
+
+    /// Terms of the stored columns of each row of a CSR matrix.
+    #[pyo3(name = "inverse_transform")]
+    fn py_inverse_transform(
+        &self,
+        indptr: PyReadonlyArray1<'_, i64>,
+        indices: PyReadonlyArray1<'_, i64>,
+    ) -> PyResult<Vec<Vec<String>>> {
+        Ok(self.inverse_transform(indptr.as_slice()?, indices.as_slice()?)?)
     }
 
     // ---------------------------------------------------------------------
-    // Accessors
+    // Persistence
     // ---------------------------------------------------------------------
-    pub fn get_vocab(&self) -> HashMap<String, usize> {
-        self.vocab.clone()
+
+    #[pyo3(name = "save")]
+    fn py_save(&self, path: PathBuf) -> PyResult<()> {
+        Ok(self.save(path)?)
     }
 
-    pub fn get_num_cols(&self) -> usize {
-        self.num_cols
+    #[staticmethod]
+    #[pyo3(name = "load")]
+    fn py_load(path: PathBuf) -> PyResult<Self> {
+        Ok(Self::load(path)?)
+    }
+
+    #[pyo3(name = "to_json")]
+    fn py_to_json(&self) -> PyResult<String> {
+        Ok(self.to_json()?)
     }
 
     // ---------------------------------------------------------------------
@@ -159,31 +821,1525 @@ impl SparseMatrixBuilder {
     // ---------------------------------------------------------------------
 
     pub fn __setstate__(&mut self, state: &Bound<'_, PyBytes>) -> PyResult<()> {
-        *self = deserialize(state.as_bytes()).unwrap();
+        *self = Self::from_bytes(state.as_bytes())?;
         Ok(())
     }
 
     pub fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
-        Ok(PyBytes::new(py, &serialize(&self).unwrap()))
+        Ok(PyBytes::new(py, &self.to_bytes()?))
     }
 
-    pub fn __getnewargs__(
-        &self,
-    ) -> PyResult<(Vec<usize>, String, Option<Vec<String>>, Option<bool>)> {
+    pub fn __getnewargs__(&self) -> PyResult<NewArgs> {
         Ok((
             self.n_sizes.clone(),
-            self.analyzer.clone(),
+            self.analyzer.as_str().to_string(),
             self.stop_words.clone(),
             self.normalize,
         ))
     }
 }
 
-// ---------------------------------------------------------------------------
-// Module registration
-// ---------------------------------------------------------------------------
+#[cfg(feature = "python")]
+impl SparseMatrixBuilder {
+    /// Move a count matrix to numpy, with uint8 data in binary mode.
+    fn _counts_into_pyarrays(&self, matrix: CsrMatrix<u32>, py: Python<'_>) -> PyResult<PyObject> {
+        // Scipy csr_matrix are faster to build from numpy arrays.
+        let arrays: Bound<'_, PyAny> = if self.binary {
+            matrix.into_binary().into_pyarrays(py).into_pyobject(py)?
+        } else {
+            matrix.into_pyarrays(py).into_pyobject(py)?
+        }
+        .into_any();
+        Ok(arrays.unbind())
+    }
+}
 
-pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
-    m.add_class::<SparseMatrixBuilder>()?;
-    Ok(())
+impl SparseMatrixBuilder {
+    /// Count the n-grams of each text with the builder analyzer settings.
+    fn _vectorize(&self, texts: Vec<String>) -> Result<Vec<HashMap<String, usize>>, LeNLPError> {
+        rsvectorize_many(
+            texts,
+            self.n_sizes.clone(),
+            self.analyzer,
+            self.stop_words.clone(),
+            self.normalize,
+            self.binary,
+        )
+    }
+
+    fn _fit(&mut self, texts: Vec<HashMap<String, usize>>) -> Result<(), LeNLPError> {
+        self._fit_counts(TermCounts::collect(&texts))?;
+        self.document_lengths = self._document_lengths(&texts);
+        Ok(())
+    }
+
+    /// Number of in-vocabulary tokens of each document.
+    fn _document_lengths(&self, texts: &[HashMap<String, usize>]) -> Vec<usize> {
+        texts
+            .par_iter()
+            .map(|doc: &HashMap<String, usize>| {
+                doc.iter()
+                    .filter(|(token, _)| self.vocab.contains_key(*token))
+                    .map(|(_, &count)| count)
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Prune the collected terms and assign them column indices.
+    fn _fit_counts(&mut self, counts: TermCounts) -> Result<(), LeNLPError> {
+        self.legacy_idf = None;
+        self.bm25_adpt = None;
+        if self.fixed_vocabulary {
+            self._fit_fixed_counts(counts);
+            return Ok(());
+        }
+
+        let n_documents: usize = counts.n_documents;
+        let min_count: f64 = self.min_df.resolve(n_documents);
+        let max_count: f64 = self.max_df.resolve(n_documents);
+        if max_count < min_count {
+            return Err(LeNLPError::InvalidParameter(
+                "max_df corresponds to < documents than min_df".to_string(),
+            ));
+        }
+
+        let mut kept: Vec<usize> = (0..counts.terms.len())
+            .filter(|&term_idx| {
+                let df: f64 = counts.document_frequencies[term_idx] as f64;
+                df >= min_count && df <= max_count
+            })
+            .collect();
+
+        if let Some(max_features) = self.max_features {
+            if kept.len() > max_features {
+                ColumnOrder::Frequency.sort(&mut kept, &counts);
+                kept.truncate(max_features);
+            }
+        }
+
+        self.column_order.sort(&mut kept, &counts);
+
+        let mut is_kept: Vec<bool> = vec![false; counts.terms.len()];
+        for &term_idx in &kept {
+            is_kept[term_idx] = true;
+        }
+
+        self.vocab = HashMap::with_capacity(kept.len());
+        self.terms = Vec::with_capacity(kept.len());
+        self.document_frequencies = Vec::with_capacity(kept.len());
+        self.collection_frequencies = Vec::with_capacity(kept.len());
+        self.n_tokens = 0;
+        for (col_idx, &term_idx) in kept.iter().enumerate() {
+            self.vocab.insert(counts.terms[term_idx].clone(), col_idx);
+            self.terms.push(counts.terms[term_idx].clone());
+            self.document_frequencies
+                .push(counts.document_frequencies[term_idx]);
+            self.collection_frequencies
+                .push(counts.corpus_frequencies[term_idx]);
+            self.n_tokens += counts.corpus_frequencies[term_idx];
+        }
+
+        self.pruned_terms = counts
+            .terms
+            .iter()
+            .cloned()
+            .zip(is_kept)
+            .filter_map(|(term, is_kept)| (!is_kept).then_some(term))
+            .collect();
+        self.pruned_terms.sort_unstable();
+
+        self.num_cols = kept.len();
+        self.n_documents = n_documents;
+        self.document_lengths = counts.document_lengths;
+        self._update_statistics();
+        Ok(())
+    }
+
+    /// Compute the document statistics of the fixed vocabulary, other terms are ignored.
+    fn _fit_fixed_counts(&mut self, counts: TermCounts) {
+        self.document_frequencies = vec![0; self.num_cols];
+        self.collection_frequencies = vec![0; self.num_cols];
+        self.n_tokens = 0;
+        for (term_idx, term) in counts.terms.iter().enumerate() {
+            if let Some(&col_idx) = self.vocab.get(term) {
+                self.document_frequencies[col_idx] = counts.document_frequencies[term_idx];
+                self.collection_frequencies[col_idx] = counts.corpus_frequencies[term_idx];
+                self.n_tokens += counts.corpus_frequencies[term_idx];
+            }
+        }
+
+        self.n_documents = counts.n_documents;
+        self.document_lengths = counts.document_lengths;
+        self._update_statistics();
+    }
+
+    /// Index the terms by column, terms must be unique.
+    fn _set_vocabulary(&mut self, terms: Vec<String>) -> Result<(), LeNLPError> {
+        self.vocab = terms
+            .iter()
+            .enumerate()
+            .map(|(col_idx, term)| (term.clone(), col_idx))
+            .collect();
+        if self.vocab.len() != terms.len() {
+            return Err(LeNLPError::InvalidParameter(
+                "vocabulary contains repeated terms.".to_string(),
+            ));
+        }
+
+        self.num_cols = terms.len();
+        self.terms = terms;
+        Ok(())
+    }
+
+    fn _partial_fit(&mut self, texts: Vec<HashMap<String, usize>>) {
+        self.legacy_idf = None;
+        self.bm25_adpt = None;
+        let counts: TermCounts = TermCounts::collect(&texts);
+
+        let mut new_terms: Vec<usize> = Vec::new();
+        for (term_idx, term) in counts.terms.iter().enumerate() {
+            match self.vocab.get(term) {
+                Some(&col_idx) => {
+                    self.document_frequencies[col_idx] += counts.document_frequencies[term_idx];
+                    self.collection_frequencies[col_idx] += counts.corpus_frequencies[term_idx];
+                    self.n_tokens += counts.corpus_frequencies[term_idx];
+                }
+                None if !self.fixed_vocabulary => new_terms.push(term_idx),
+                None => {}
+            }
+        }
+
+        self.column_order.sort(&mut new_terms, &counts);
+
+        for term_idx in new_terms {
+            self.vocab
+                .insert(counts.terms[term_idx].clone(), self.num_cols);
+            self.terms.push(counts.terms[term_idx].clone());
+            self.document_frequencies
+                .push(counts.document_frequencies[term_idx]);
+            self.collection_frequencies
+                .push(counts.corpus_frequencies[term_idx]);
+            self.n_tokens += counts.corpus_frequencies[term_idx];
+            self.num_cols += 1;
+        }
+        // Terms pruned by the last fit are no longer ignored once they have a column.
+        let vocab: &HashMap<String, usize> = &self.vocab;
+        self.pruned_terms.retain(|term| !vocab.contains_key(term));
+
+        self.n_documents += texts.len();
+        let document_lengths: Vec<usize> = self._document_lengths(&texts);
+        self.document_lengths.extend(document_lengths);
+        self._update_statistics();
+    }
+
+    /// Refresh the statistics derived from the document frequencies and token counts.
+    fn _update_statistics(&mut self) {
+        self.average_len = if self.n_documents == 0 {
+            0.0
+        } else {
+            self.n_tokens as f32 / self.n_documents as f32
+        };
+        self._update_idf();
+    }
+
+    /// Compute the idf of each column from the fitted document frequencies.
+    fn _update_idf(&mut self) {
+        if let Some(idf) = &self.legacy_idf {
+            self.idf = idf.clone();
+            return;
+        }
+        let n_documents: f32 = self.n_documents as f32;
+        let smooth: f32 = if self.smooth_idf { 1.0 } else { 0.0 };
+        self.idf = self
+            .document_frequencies
+            .iter()
+            .map(|&df| ((n_documents + smooth) / (df as f32 + smooth)).ln() + 1.0)
+            .collect();
+    }
+
+    /// BM25 idf of each column, computed from the fitted document frequencies.
+    fn bm25_idf(&self, idf: Bm25Idf) -> Vec<f32> {
+        let n_documents: f32 = self.n_documents as f32;
+        self.document_frequencies
+            .iter()
+            .map(|&df| idf.weight(n_documents, df as f32))
+            .collect()
+    }
+
+    /// Check that BM25-adpt parameters were fitted with the length normalization `b`.
+    fn _check_bm25_adpt(&self, b: f32, variant: Bm25Variant) -> Result<(), LeNLPError> {
+        match (variant, &self.bm25_adpt) {
+            (Bm25Variant::Adpt, None) => Err(LeNLPError::InvalidParameter(
+                "The 'bm25-adpt' variant needs per-term parameters, call fit_bm25_adpt or \
+                 fit_transform_bm25 first."
+                    .to_string(),
+            )),
+            (Bm25Variant::Adpt, Some(bm25_adpt)) if bm25_adpt.b != b => {
+                Err(LeNLPError::InvalidParameter(format!(
+                    "BM25-adpt parameters were fitted with b={}, got b={}.",
+                    bm25_adpt.b, b
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Fit the k1 and the weight of every column for BM25-adpt (Lv and Zhai, 2011).
+    ///
+    /// With `ctd` the term frequency divided by `1 - b + b * len / average_len` and
+    /// `df_r` the number of documents where `ctd >= r - 0.5`, `df_0` being the number
+    /// of documents, the information gain of a term reaching `r + 1` occurrences is
+    /// `G_r = log2((df_{r+1} + 0.5) / (df_r + 1)) - log2((df + 0.5) / (n + 1))`.
+    /// `G_1` weights the term and k1 minimizes the squared error between `G_r / G_1`
+    /// and `(k1 + 1) r / (k1 + r)` over the observed `r >= 2`.
+    fn _fit_bm25_adpt(&self, texts: Vec<HashMap<String, usize>>, b: f32) -> Bm25AdptTerms {
+        // Number of documents by column and largest r such that ctd >= r - 0.5.
+        let mut histograms: Vec<Vec<usize>> = vec![Vec::new(); self.num_cols];
+        for row in self._weight_rows(texts) {
+            let len: f32 = row.iter().map(|&(_, count)| count).sum();
+            let relative_len: f32 = if self.average_len > 0.0 {
+                len / self.average_len
+            } else {
+                0.0
+            };
+            let length_norm: f32 = 1.0 - b + b * relative_len;
+            for (col_idx, tf) in row {
+                let ctd: f32 = if length_norm > 0.0 {
+                    tf / length_norm
+                } else {
+                    tf
+                };
+                let r: usize = (ctd + 0.5).floor() as usize;
+                let histogram: &mut Vec<usize> = &mut histograms[col_idx];
+                if histogram.len() <= r {
+                    histogram.resize(r + 1, 0);
+                }
+                histogram[r] += 1;
+            }
+        }
+
+        let n_documents: f64 = self.n_documents as f64;
+        let (k1, weights): (Vec<Option<f32>>, Vec<f32>) = histograms
+            .into_par_iter()
+            .zip(self.document_frequencies.par_iter())
+            .map(|(histogram, &df)| {
+                // df_r for r >= 1 is the number of documents reaching at least r.
+                let mut df_r: Vec<f64> = vec![0.0; histogram.len().max(3)];
+                df_r[0] = n_documents;
+                let mut reached: usize = 0;
+                for r in (1..histogram.len()).rev() {
+                    reached += histogram[r];
+                    df_r[r] = reached as f64;
+                }
+                let baseline: f64 = ((df as f64 + 0.5) / (n_documents + 1.0)).log2();
+                let gain = |r: usize| ((df_r[r + 1] + 0.5) / (df_r[r] + 1.0)).log2() - baseline;
+
+                let g1: f64 = gain(1);
+                if g1 <= 0.0 {
+                    return (None, 0.0);
+                }
+                let ratios: Vec<(f64, f64)> = (2..df_r.len() - 1)
+                    .filter(|&r| df_r[r + 1] > 0.0)
+                    .map(|r| (r as f64, gain(r) / g1))
+                    .collect();
+                (fit_k1(&ratios), g1 as f32)
+            })
+            .unzip();
+
+        Bm25AdptTerms { b, k1, weights }
+    }
+
+    fn _transform(&self, texts: Vec<HashMap<String, usize>>) -> CsrMatrix<u32> {
+        CsrMatrix::from_rows(self._count_rows(texts))
+    }
+
+    /// Count the in-vocabulary tokens of each document as (column, count) pairs
+    /// sorted by column.
+    fn _count_rows(&self, texts: Vec<HashMap<String, usize>>) -> Vec<Vec<(usize, u32)>> {
+        texts
+            .par_iter()
+            .map(|doc: &HashMap<String, usize>| {
+                let mut row: Vec<(usize, u32)> = doc
+                    .iter()
+                    .filter_map(|(token, &count)| {
+                        self.vocab
+                            .get(token)
+                            .map(|&col_idx| (col_idx, count as u32))
+                    })
+                    .collect();
+                row.sort_unstable_by_key(|&(col_idx, _)| col_idx);
+                row
+            })
+            .collect()
+    }
+
+    /// Count the in-vocabulary tokens of each document as float (column, count) pairs.
+    fn _weight_rows(&self, texts: Vec<HashMap<String, usize>>) -> Vec<Vec<(usize, f32)>> {
+        self._count_rows(texts)
+            .into_par_iter()
+            .map(|row: Vec<(usize, u32)>| {
+                row.into_iter()
+                    .map(|(col_idx, count)| (col_idx, count as f32))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Compute BM25 weights of each document, L2-normalized by row.
+    fn _transform_bm25(
+        &self,
+        texts: Vec<HashMap<String, usize>>,
+        params: Bm25Params,
+    ) -> CsrMatrix<f32> {
+        let Bm25Params {
+            k1,
+            b,
+            epsilon,
+            variant,
+            idf,
+        } = params;
+        // BM25-adpt replaces the idf and k1 of each term by their fitted values.
+        let bm25_adpt: Option<&Bm25AdptTerms> = match variant {
+            Bm25Variant::Adpt => self.bm25_adpt.as_ref(),
+            _ => None,
+        };
+        let idf: Vec<f32> = match bm25_adpt {
+            Some(bm25_adpt) => bm25_adpt.weights.clone(),
+            None => self.bm25_idf(idf),
+        };
+        let k1_of = |col_idx: usize| -> f32 {
+            bm25_adpt
+                .and_then(|bm25_adpt| bm25_adpt.k1[col_idx])
+                .unwrap_or(k1)
+        };
+
+        let mut rows: Vec<Vec<(usize, f32)>> = self._weight_rows(texts);
+        rows.par_iter_mut().for_each(|row: &mut Vec<(usize, f32)>| {
+            let len: f32 = row.iter().map(|&(_, count)| count).sum();
+            let relative_len: f32 = if self.average_len > 0.0 {
+                len / self.average_len
+            } else {
+                0.0
+            };
+            let length_norm: f32 = 1.0 - b + b * relative_len;
+
+            for (col_idx, value) in row.iter_mut() {
+                *value =
+                    (variant.tf(*value, k1_of(*col_idx), length_norm) + epsilon) * idf[*col_idx];
+            }
+
+            normalize_row(row, Norm::L2);
+        });
+
+        CsrMatrix::from_rows(rows)
+    }
+
+    /// Compute tf-idf weights of each document, normalized by row.
+    fn _transform_tfidf(
+        &self,
+        texts: Vec<HashMap<String, usize>>,
+        sublinear_tf: bool,
+        use_idf: bool,
+        norm: Option<Norm>,
+    ) -> CsrMatrix<f32> {
+        let mut rows: Vec<Vec<(usize, f32)>> = self._weight_rows(texts);
+        rows.par_iter_mut().for_each(|row: &mut Vec<(usize, f32)>| {
+            for (col_idx, value) in row.iter_mut() {
+                if sublinear_tf {
+                    *value = value.ln() + 1.0;
+                }
+                if use_idf {
+                    *value *= self.idf[*col_idx];
+                }
+            }
+
+            if let Some(norm) = norm {
+                normalize_row(row, norm);
+            }
+        });
+
+        CsrMatrix::from_rows(rows)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Weighting helpers
+// ---------------------------------------------------------------------------
+
+/// Document and corpus frequencies and first document of every term of a corpus.
+pub(crate) struct TermCounts {
+    pub(crate) terms: Vec<String>,
+    term_index: HashMap<String, usize>,
+    pub(crate) document_frequencies: Vec<usize>,
+    corpus_frequencies: Vec<usize>,
+    first_documents: Vec<usize>,
+    pub(crate) n_documents: usize,
+    /// Number of tokens of each document, whether their terms are kept or not.
+    document_lengths: Vec<usize>,
+}
+
+impl TermCounts {
+    fn new() -> TermCounts {
+        TermCounts {
+            terms: Vec::new(),
+            term_index: HashMap::new(),
+            document_frequencies: Vec::new(),
+            corpus_frequencies: Vec::new(),
+            first_documents: Vec::new(),
+            n_documents: 0,
+            document_lengths: Vec::new(),
+        }
+    }
+
+    pub(crate) fn collect(texts: &[HashMap<String, usize>]) -> TermCounts {
+        let mut counts: TermCounts = TermCounts::new();
+        counts.update(texts);
+        counts
+    }
+
+    /// Accumulate the statistics of a batch of documents.
+    fn update(&mut self, texts: &[HashMap<String, usize>]) {
+        for doc in texts {
+            for (token, &count) in doc.iter() {
+                let term_idx: usize = match self.term_index.get(token) {
+                    Some(&term_idx) => term_idx,
+                    None => {
+                        self.term_index.insert(token.clone(), self.terms.len());
+                        self.terms.push(token.clone());
+                        self.document_frequencies.push(0);
+                        self.corpus_frequencies.push(0);
+                        self.first_documents.push(self.n_documents);
+                        self.terms.len() - 1
+                    }
+                };
+                self.document_frequencies[term_idx] += 1;
+                self.corpus_frequencies[term_idx] += count;
+            }
+            self.document_lengths.push(doc.values().sum());
+            self.n_documents += 1;
+        }
+    }
+}
+
+/// Policy used to assign column indices to the fitted terms.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnOrder {
+    Lexicographic,
+    FirstOccurrence,
+    Frequency,
+}
+
+impl ColumnOrder {
+    /// Sort term indices following the policy, ties are broken lexicographically so
+    /// that identical corpora give identical columns.
+    pub(crate) fn sort(&self, term_indices: &mut [usize], counts: &TermCounts) {
+        let terms: &[String] = &counts.terms;
+        match self {
+            ColumnOrder::Lexicographic => term_indices.sort_by(|&a, &b| terms[a].cmp(&terms[b])),
+            ColumnOrder::FirstOccurrence => term_indices.sort_by(|&a, &b| {
+                counts.first_documents[a]
+                    .cmp(&counts.first_documents[b])
+                    .then_with(|| terms[a].cmp(&terms[b]))
+            }),
+            ColumnOrder::Frequency => term_indices.sort_by(|&a, &b| {
+                counts.corpus_frequencies[b]
+                    .cmp(&counts.corpus_frequencies[a])
+                    .then_with(|| terms[a].cmp(&terms[b]))
+            }),
+        }
+    }
+}
+
+impl FromStr for ColumnOrder {
+    type Err = LeNLPError;
+
+    fn from_str(column_order: &str) -> Result<Self, Self::Err> {
+        match column_order {
+            "lexicographic" => Ok(ColumnOrder::Lexicographic),
+            "first_occurrence" => Ok(ColumnOrder::FirstOccurrence),
+            "frequency" => Ok(ColumnOrder::Frequency),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid column_order '{}', expected 'lexicographic', 'first_occurrence' or 'frequency'.",
+                column_order
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for ColumnOrder {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<ColumnOrder>()?)
+    }
+}
+
+/// Document frequency bound, either a number of documents or a proportion of the corpus.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "python", derive(FromPyObject))]
+pub enum DocumentFrequency {
+    Absolute(usize),
+    Proportion(f64),
+}
+
+impl DocumentFrequency {
+    fn validate(&self, name: &str) -> Result<(), LeNLPError> {
+        match self {
+            DocumentFrequency::Proportion(value) if !(0.0..=1.0).contains(value) => {
+                Err(LeNLPError::InvalidParameter(format!(
+                    "{} must be in [0.0, 1.0] when a float.",
+                    name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of documents the bound corresponds to.
+    fn resolve(&self, n_documents: usize) -> f64 {
+        match *self {
+            DocumentFrequency::Absolute(count) => count as f64,
+            DocumentFrequency::Proportion(proportion) => proportion * n_documents as f64,
+        }
+    }
+}
+
+/// Vocabulary given by the user, either a term to column mapping or terms ordered by
+/// column.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "python", derive(FromPyObject))]
+pub enum Vocabulary {
+    Mapping(HashMap<String, usize>),
+    Terms(Vec<String>),
+}
+
+impl Vocabulary {
+    /// Terms ordered by column, the columns of a mapping must be 0 to n - 1.
+    fn into_terms(self) -> Result<Vec<String>, LeNLPError> {
+        match self {
+            Vocabulary::Terms(terms) => Ok(terms),
+            Vocabulary::Mapping(mapping) => {
+                let mut terms: Vec<Option<String>> = vec![None; mapping.len()];
+                for (term, col_idx) in mapping {
+                    match terms.get_mut(col_idx) {
+                        Some(slot @ None) => *slot = Some(term),
+                        _ => {
+                            return Err(LeNLPError::InvalidParameter(format!(
+                            "vocabulary indices must be unique and in [0, {}), got {} for '{}'.",
+                            terms.len(),
+                            col_idx,
+                            term
+                        )))
+                        }
+                    }
+                }
+                Ok(terms.into_iter().flatten().collect())
+            }
+        }
+    }
+}
+
+/// Row normalization applied to weighted matrices.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Norm {
+    L1,
+    L2,
+}
+
+impl FromStr for Norm {
+    type Err = LeNLPError;
+
+    fn from_str(norm: &str) -> Result<Self, Self::Err> {
+        match norm {
+            "l1" => Ok(Norm::L1),
+            "l2" => Ok(Norm::L2),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid norm '{}', expected 'l1', 'l2' or None.",
+                norm
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Norm {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<Norm>()?)
+    }
+}
+
+/// Term frequency saturation used by BM25 weighting.
+///
+/// `Plus` adds a lower bound `delta` to the weight of every matching term so that
+/// long documents are not scored below documents missing the term. `L` shifts the
+/// length-normalized term frequency by `delta` before saturation. `Adpt` uses the
+/// k1 and weight fitted for each term by `SparseMatrixBuilder::fit_bm25_adpt`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Bm25Variant {
+    #[default]
+    Okapi,
+    Plus {
+        delta: f32,
+    },
+    L {
+        delta: f32,
+    },
+    Adpt,
+}
+
+impl Bm25Variant {
+    /// Parse a variant name, `delta` overrides the default of BM25+ (1.0) and
+    /// BM25L (0.5).
+    pub fn new(variant: &str, delta: Option<f32>) -> Result<Self, LeNLPError> {
+        let variant: Bm25Variant = variant.parse()?;
+        let variant: Bm25Variant = match (variant, delta) {
+            (Bm25Variant::Okapi | Bm25Variant::Adpt, Some(_)) => {
+                return Err(LeNLPError::InvalidParameter(
+                    "delta is only used by the 'bm25+' and 'bm25l' variants.".to_string(),
+                ))
+            }
+            (Bm25Variant::Plus { .. }, Some(delta)) => Bm25Variant::Plus { delta },
+            (Bm25Variant::L { .. }, Some(delta)) => Bm25Variant::L { delta },
+            (variant, None) => variant,
+        };
+        variant.validate()?;
+        Ok(variant)
+    }
+
+    fn validate(&self) -> Result<(), LeNLPError> {
+        match self {
+            Bm25Variant::Plus { delta } | Bm25Variant::L { delta }
+                if !(delta.is_finite() && *delta >= 0.0) =>
+            {
+                Err(LeNLPError::InvalidParameter(format!(
+                    "delta must be a non-negative number, got {}.",
+                    delta
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Saturated weight of a term occurring `tf` times in a document whose length
+    /// normalization is `1 - b + b * len / average_len`.
+    fn tf(&self, tf: f32, k1: f32, length_norm: f32) -> f32 {
+        match *self {
+            Bm25Variant::Okapi | Bm25Variant::Adpt => (tf * (k1 + 1.0)) / (tf + k1 * length_norm),
+            Bm25Variant::Plus { delta } => (tf * (k1 + 1.0)) / (tf + k1 * length_norm) + delta,
+            Bm25Variant::L { delta } => {
+                let tf: f32 = if length_norm > 0.0 {
+                    tf / length_norm
+                } else {
+                    tf
+                };
+                ((k1 + 1.0) * (tf + delta)) / (k1 + tf + delta)
+            }
+        }
+    }
+}
+
+impl FromStr for Bm25Variant {
+    type Err = LeNLPError;
+
+    fn from_str(variant: &str) -> Result<Self, Self::Err> {
+        match variant {
+            "okapi" => Ok(Bm25Variant::Okapi),
+            "bm25+" => Ok(Bm25Variant::Plus { delta: 1.0 }),
+            "bm25l" => Ok(Bm25Variant::L { delta: 0.5 }),
+            "bm25-adpt" => Ok(Bm25Variant::Adpt),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid BM25 variant '{}', expected 'okapi', 'bm25+', 'bm25l' or 'bm25-adpt'.",
+                variant
+            ))),
+        }
+    }
+}
+
+/// Per-term parameters of BM25-adpt fitted on a corpus.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bm25AdptTerms {
+    /// Length normalization the term frequencies were fitted with.
+    pub b: f32,
+    /// k1 of each column, `None` when no document repeats the term enough to fit it.
+    pub k1: Vec<Option<f32>>,
+    /// Information gain of a first occurrence of each column, clipped at zero, used as
+    /// its idf.
+    pub weights: Vec<f32>,
+}
+
+/// k1 in `[0, 10]` minimizing the squared error between the (r, ratio) points and
+/// `(k1 + 1) r / (k1 + r)`, by golden-section search.
+fn fit_k1(ratios: &[(f64, f64)]) -> Option<f32> {
+    if ratios.is_empty() {
+        return None;
+    }
+    let error = |k1: f64| -> f64 {
+        ratios
+            .iter()
+            .map(|&(r, ratio)| (ratio - (k1 + 1.0) * r / (k1 + r)).powi(2))
+            .sum()
+    };
+
+    let inverse_phi: f64 = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high): (f64, f64) = (0.0, 10.0);
+    for _ in 0..64 {
+        let left: f64 = high - inverse_phi * (high - low);
+        let right: f64 = low + inverse_phi * (high - low);
+        if error(left) <= error(right) {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    Some(((low + high) / 2.0) as f32)
+}
+
+/// Parameters of BM25 weighting, the defaults are those of `BM25Vectorizer`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bm25Params {
+    /// How quickly the impact of term frequency saturates.
+    pub k1: f32,
+    /// Impact of the document length normalization.
+    pub b: f32,
+    /// Smoothing term added to the saturated term frequency.
+    pub epsilon: f32,
+    pub variant: Bm25Variant,
+    pub idf: Bm25Idf,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Bm25Params {
+            k1: 1.5,
+            b: 0.75,
+            epsilon: 0.0,
+            variant: Bm25Variant::Okapi,
+            idf: Bm25Idf::Lucene,
+        }
+    }
+}
+
+/// Inverse document frequency used by BM25 weighting.
+///
+/// `Lucene` is `ln(1 + (N - df + 0.5) / (df + 0.5))`, always positive. `Atire` is
+/// `ln(N / df)`, terms absent from the fitted corpus get a weight of 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Bm25Idf {
+    #[default]
+    Lucene,
+    Atire,
+}
+
+impl Bm25Idf {
+    pub(crate) fn weight(&self, n_documents: f32, df: f32) -> f32 {
+        match self {
+            Bm25Idf::Lucene => ((n_documents - df + 0.5) / (df + 0.5) + 1.0).ln(),
+            Bm25Idf::Atire if df > 0.0 => (n_documents / df).ln(),
+            Bm25Idf::Atire => 0.0,
+        }
+    }
+}
+
+impl FromStr for Bm25Idf {
+    type Err = LeNLPError;
+
+    fn from_str(idf: &str) -> Result<Self, Self::Err> {
+        match idf {
+            "lucene" => Ok(Bm25Idf::Lucene),
+            "atire" => Ok(Bm25Idf::Atire),
+            _ => Err(LeNLPError::InvalidParameter(format!(
+                "Invalid BM25 idf '{}', expected 'lucene' or 'atire'.",
+                idf
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<'py> FromPyObject<'py> for Bm25Idf {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        Ok(ob.extract::<String>()?.parse::<Bm25Idf>()?)
+    }
+}
+
+/// Divide the values of a row by their L1 or L2 norm.
+pub fn normalize_row(row: &mut [(usize, f32)], norm: Norm) {
+    let norm: f32 = match norm {
+        Norm::L1 => row.iter().map(|&(_, value)| value.abs()).sum(),
+        Norm::L2 => row
+            .iter()
+            .map(|&(_, value)| value * value)
+            .sum::<f32>()
+            .sqrt(),
+    };
+    if norm > 0.0 {
+        for (_, value) in row.iter_mut() {
+            *value /= norm;
+        }
+    }
+}
+
+/// Sparse matrix in CSR format with sorted column indices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsrMatrix<T> {
+    pub indptr: Vec<i64>,
+    pub indices: Vec<i64>,
+    pub data: Vec<T>,
+}
+
+impl<T: Copy + Send + Sync> CsrMatrix<T> {
+    /// Build a CSR matrix from its arrays, checking that `indptr` starts at 0, is
+    /// non-decreasing and ends at the number of stored values.
+    pub fn new(indptr: Vec<i64>, indices: Vec<i64>, data: Vec<T>) -> Result<Self, LeNLPError> {
+        let is_valid: bool = !indptr.is_empty()
+            && indptr[0] == 0
+            && indptr.windows(2).all(|bounds| bounds[0] <= bounds[1])
+            && indptr[indptr.len() - 1] as usize == indices.len()
+            && indices.len() == data.len()
+            && indices.iter().all(|&col_idx| col_idx >= 0);
+        if !is_valid {
+            return Err(LeNLPError::InvalidParameter(
+                "indptr must start at 0, be non-decreasing and end at the number of values, \
+                 indices must be non-negative."
+                    .to_string(),
+            ));
+        }
+        Ok(CsrMatrix {
+            indptr,
+            indices,
+            data,
+        })
+    }
+
+    /// Number of rows of the matrix.
+    pub fn n_rows(&self) -> usize {
+        self.indptr.len() - 1
+    }
+
+    /// Build a CSR matrix from rows of (column, value) pairs sorted by column.
+    pub fn from_rows(rows: Vec<Vec<(usize, T)>>) -> Self {
+        let mut indptr: Vec<i64> = Vec::with_capacity(rows.len() + 1);
+        indptr.push(0);
+        for row in &rows {
+            indptr.push(indptr[indptr.len() - 1] + row.len() as i64);
+        }
+
+        let nnz: usize = indptr[rows.len()] as usize;
+        let mut indices: Vec<i64> = Vec::with_capacity(nnz);
+        let mut data: Vec<T> = Vec::with_capacity(nnz);
+        for row in rows {
+            for (col_idx, value) in row {
+                indices.push(col_idx as i64);
+                data.push(value);
+            }
+        }
+
+        CsrMatrix {
+            indptr,
+            indices,
+            data,
+        }
+    }
+}
+
+impl CsrMatrix<u32> {
+    /// Convert a count matrix to a presence matrix, stored values become 1.
+    pub fn into_binary(self) -> CsrMatrix<u8> {
+        CsrMatrix {
+            data: vec![1; self.data.len()],
+            indptr: self.indptr,
+            indices: self.indices,
+        }
+    }
+}
+
+#[cfg(feature = "python")]
+impl<T: Element> CsrMatrix<T> {
+    /// Move the arrays to numpy, ready for `scipy.sparse.csr_matrix((data, indices, indptr))`.
+    pub fn into_pyarrays(self, py: Python<'_>) -> Csr<T> {
+        (
+            PyArray1::from_vec(py, self.indptr).into(),
+            PyArray1::from_vec(py, self.indices).into(),
+            PyArray1::from_vec(py, self.data).into(),
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Module registration
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "python")]
+pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<SparseMatrixBuilder>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(tokens: &[&str]) -> HashMap<String, usize> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for token in tokens {
+            *counts.entry(token.to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn builder(
+        min_df: DocumentFrequency,
+        max_df: DocumentFrequency,
+        max_features: Option<usize>,
+        column_order: &str,
+    ) -> SparseMatrixBuilder {
+        SparseMatrixBuilder::new(SparseMatrixBuilderOptions {
+            min_df,
+            max_df,
+            max_features,
+            column_order: column_order.parse().unwrap(),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn default_builder() -> SparseMatrixBuilder {
+        builder(
+            DocumentFrequency::Absolute(1),
+            DocumentFrequency::Proportion(1.0),
+            None,
+            "first_occurrence",
+        )
+    }
+
+    /// Value of a cell of a CSR matrix, zero when not stored.
+    fn cell(matrix: &CsrMatrix<f32>, row_idx: usize, col_idx: usize) -> f32 {
+        let start: usize = matrix.indptr[row_idx] as usize;
+        let end: usize = matrix.indptr[row_idx + 1] as usize;
+        (start..end)
+            .find(|&idx| matrix.indices[idx] == col_idx as i64)
+            .map(|idx| matrix.data[idx])
+            .unwrap_or(0.0)
+    }
+
+    #[test]
+    fn test_fit_statistics() {
+        let mut builder = default_builder();
+        builder
+            ._fit(vec![count(&["hello", "world"]), count(&["hello", "hello"])])
+            .unwrap();
+
+        assert_eq!(builder.num_cols, 2);
+        assert_eq!(builder.n_documents, 2);
+        assert_eq!(builder.average_len, 2.0);
+        assert_eq!(builder.document_frequencies[builder.vocab["hello"]], 2);
+        assert_eq!(builder.document_frequencies[builder.vocab["world"]], 1);
+        assert_eq!(builder.collection_frequencies(), [3, 1]);
+        assert_eq!(builder.document_lengths(), [2, 2]);
+    }
+
+    #[test]
+    fn test_transform_csr() {
+        let mut builder = builder(
+            DocumentFrequency::Absolute(1),
+            DocumentFrequency::Proportion(1.0),
+            None,
+            "lexicographic",
+        );
+        let texts = vec![count(&["c", "a", "b", "a"]), count(&[]), count(&["b", "z"])];
+        builder._fit(texts[..1].to_vec()).unwrap();
+
+        let matrix: CsrMatrix<u32> = builder._transform(texts);
+        assert_eq!(matrix.indptr, vec![0, 3, 3, 4]);
+        assert_eq!(matrix.indices, vec![0, 1, 2, 1]);
+        assert_eq!(matrix.data, vec![2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_transform_bm25() {
+        let mut builder = default_builder();
+        let texts = vec![count(&["hello", "world"]), count(&["hello", "hello"])];
+        builder._fit(texts.clone()).unwrap();
+
+        let matrix: CsrMatrix<f32> = builder._transform_bm25(texts, Bm25Params::default());
+        assert_eq!(matrix.data.len(), 3);
+
+        // Each row is L2-normalized.
+        for row_idx in 0..2 {
+            let start: usize = matrix.indptr[row_idx] as usize;
+            let end: usize = matrix.indptr[row_idx + 1] as usize;
+            let norm: f32 = matrix.data[start..end]
+                .iter()
+                .map(|value| value * value)
+                .sum();
+            assert!((norm - 1.0).abs() < 1e-6);
+        }
+
+        // "world" is rarer than "hello" so it weighs more in the first document.
+        assert!(
+            cell(&matrix, 0, builder.vocab["world"]) > cell(&matrix, 0, builder.vocab["hello"])
+        );
+    }
+
+    #[test]
+    fn test_bm25_variants() {
+        let mut builder = default_builder();
+        let texts = vec![
+            count(&["hello", "world"]),
+            count(&["hello", "hello", "foo"]),
+        ];
+        builder._fit(texts.clone()).unwrap();
+        let bm25 = |variant: Bm25Variant, idf: Bm25Idf| {
+            let params = Bm25Params {
+                variant,
+                idf,
+                ..Default::default()
+            };
+            builder._transform_bm25(texts.clone(), params)
+        };
+
+        // BM25+ without lower bound is Okapi BM25.
+        assert_eq!(
+            bm25(Bm25Variant::Plus { delta: 0.0 }, Bm25Idf::Lucene),
+            bm25(Bm25Variant::Okapi, Bm25Idf::Lucene)
+        );
+        assert_ne!(
+            bm25(Bm25Variant::new("bm25+", None).unwrap(), Bm25Idf::Lucene),
+            bm25(Bm25Variant::Okapi, Bm25Idf::Lucene)
+        );
+
+        // BM25L shifts the length-normalized term frequency before saturation.
+        let variant: Bm25Variant = Bm25Variant::new("bm25l", None).unwrap();
+        assert_eq!(variant, Bm25Variant::L { delta: 0.5 });
+        assert!((variant.tf(1.0, 1.5, 1.0) - 1.25).abs() < 1e-6);
+        assert!(variant.tf(2.0, 1.5, 2.0) < variant.tf(2.0, 1.5, 1.0));
+
+        // ATIRE idf of a term found in every document is ln(1) = 0.
+        let matrix: CsrMatrix<f32> = bm25(Bm25Variant::Okapi, "atire".parse().unwrap());
+        assert_eq!(cell(&matrix, 0, builder.vocab["hello"]), 0.0);
+        assert_eq!(cell(&matrix, 0, builder.vocab["world"]), 1.0);
+
+        assert!(Bm25Variant::new("okapi", Some(1.0)).is_err());
+        assert!(Bm25Variant::new("bm25+", Some(-1.0)).is_err());
+        assert_eq!(
+            "bm25-adpt".parse::<Bm25Variant>().unwrap(),
+            Bm25Variant::Adpt
+        );
+        assert!(Bm25Variant::new("bm25-adpt", Some(1.0)).is_err());
+        assert!("robertson".parse::<Bm25Idf>().is_err());
+    }
+
+    #[test]
+    fn test_bm25_adpt() {
+        // The ratios of an exact saturation curve give back its k1.
+        let ratios: Vec<(f64, f64)> = (2..8)
+            .map(|r| (r as f64, 2.2 * r as f64 / (1.2 + r as f64)))
+            .collect();
+        assert!((fit_k1(&ratios).unwrap() - 1.2).abs() < 1e-4);
+        assert_eq!(fit_k1(&[]), None);
+
+        // "a" is repeated up to 4 times, "b" and "c" once per document.
+        let texts = vec![
+            count(&["a", "b"]),
+            count(&["a", "a", "b"]),
+            count(&["a", "a", "a", "c"]),
+            count(&["a", "a", "a", "a", "c"]),
+            count(&["b", "c"]),
+            count(&["c"]),
+        ];
+        let mut builder = default_builder();
+        builder._fit(texts.clone()).unwrap();
+        let params = Bm25Params {
+            variant: Bm25Variant::Adpt,
+            ..Default::default()
+        };
+        assert!(builder._check_bm25_adpt(params.b, params.variant).is_err());
+
+        builder.bm25_adpt = Some(builder._fit_bm25_adpt(texts.clone(), params.b));
+        let bm25_adpt: &Bm25AdptTerms = builder.bm25_adpt().unwrap();
+        let (a, b) = (builder.vocab["a"], builder.vocab["b"]);
+        assert!(bm25_adpt.k1[a].is_some_and(|k1| (0.0..=10.0).contains(&k1)));
+        assert_eq!(bm25_adpt.k1[b], None);
+        assert!(bm25_adpt.weights.iter().all(|weight| *weight >= 0.0));
+
+        let matrix: CsrMatrix<f32> = builder._transform_bm25(texts.clone(), params);
+        assert!(matrix.data.iter().all(|value| value.is_finite()));
+        assert_ne!(
+            matrix,
+            builder._transform_bm25(texts.clone(), Bm25Params::default())
+        );
+
+        // The parameters only apply to the b they were fitted with and are persisted.
+        assert!(builder._check_bm25_adpt(params.b, params.variant).is_ok());
+        assert!(builder._check_bm25_adpt(0.5, params.variant).is_err());
+        let restored = SparseMatrixBuilder::from_bytes(&builder.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.bm25_adpt(), builder.bm25_adpt());
+
+        // Fitting again discards them.
+        builder._fit(texts).unwrap();
+        assert_eq!(builder.bm25_adpt(), None);
+    }
+
+    #[test]
+    fn test_transform_tfidf() {
+        let mut builder = default_builder();
+        let texts = vec![count(&["hello", "world"]), count(&["hello", "hello"])];
+        builder._fit(texts.clone()).unwrap();
+
+        // Smoothed idf matches scikit-learn: ln((1 + n) / (1 + df)) + 1.
+        assert!((builder.idf[builder.vocab["hello"]] - 1.0).abs() < 1e-6);
+        assert!((builder.idf[builder.vocab["world"]] - (1.5_f32.ln() + 1.0)).abs() < 1e-6);
+
+        let matrix: CsrMatrix<f32> = builder._transform_tfidf(texts, true, true, Some(Norm::L1));
+        let first_row: f32 = matrix.data[..matrix.indptr[1] as usize].iter().sum();
+        assert!((first_row - 1.0).abs() < 1e-6);
+        assert_eq!(matrix.data.len(), 3);
+    }
+
+    #[test]
+    fn test_fit_pruning() {
+        let texts = vec![count(&["a", "b"]), count(&["a", "c", "c"]), count(&["a"])];
+        let fit = |min_df, max_df, max_features| {
+            let mut builder = builder(min_df, max_df, max_features, "first_occurrence");
+            builder._fit(texts.clone()).unwrap();
+            builder
+        };
+
+        let pruned = fit(
+            DocumentFrequency::Absolute(2),
+            DocumentFrequency::Proportion(1.0),
+            None,
+        );
+        assert_eq!(pruned.vocab, HashMap::from([("a".to_string(), 0)]));
+        assert_eq!(pruned.pruned_terms(), vec!["b", "c"]);
+        assert_eq!(pruned.average_len, 1.0);
+        assert_eq!(pruned.document_lengths(), [1, 1, 1]);
+
+        let pruned = fit(
+            DocumentFrequency::Absolute(1),
+            DocumentFrequency::Proportion(0.5),
+            None,
+        );
+        assert_eq!(pruned.num_cols, 2);
+        assert_eq!(pruned.pruned_terms(), vec!["a"]);
+
+        let pruned = fit(
+            DocumentFrequency::Absolute(1),
+            DocumentFrequency::Proportion(1.0),
+            Some(2),
+        );
+        assert!(pruned.vocab.contains_key("a") && pruned.vocab.contains_key("c"));
+        assert_eq!(pruned.pruned_terms(), vec!["b"]);
+
+        let mut invalid = builder(
+            DocumentFrequency::Absolute(3),
+            DocumentFrequency::Absolute(1),
+            None,
+            "first_occurrence",
+        );
+        assert!(invalid._fit(texts.clone()).is_err());
+    }
+
+    #[test]
+    fn test_column_order() {
+        let texts = vec![count(&["b", "c", "c"]), count(&["a", "c"])];
+        let fit = |column_order| {
+            let mut builder = builder(
+                DocumentFrequency::Absolute(1),
+                DocumentFrequency::Proportion(1.0),
+                None,
+                column_order,
+            );
+            builder._fit(texts.clone()).unwrap();
+            builder
+        };
+        let terms = |builder: &SparseMatrixBuilder| builder.feature_names_out().to_vec();
+
+        assert_eq!(terms(&fit("lexicographic")), vec!["a", "b", "c"]);
+        assert_eq!(terms(&fit("first_occurrence")), vec!["b", "c", "a"]);
+        assert_eq!(terms(&fit("frequency")), vec!["c", "a", "b"]);
+
+        // Each builder hashes with its own random state, serialization must not depend on it.
+        assert_eq!(
+            fit("first_occurrence").to_bytes().unwrap(),
+            fit("first_occurrence").to_bytes().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_partial_fit() {
+        let mut builder = builder(
+            DocumentFrequency::Absolute(1),
+            DocumentFrequency::Proportion(1.0),
+            None,
+            "lexicographic",
+        );
+        builder._partial_fit(vec![count(&["hello", "world"])]);
+        let before: CsrMatrix<u32> = builder._transform(vec![count(&["hello", "world"])]);
+
+        builder._partial_fit(vec![count(&["hello", "again", "again"])]);
+        assert_eq!(builder.num_cols, 3);
+        assert_eq!(builder.vocab["again"], 2);
+        assert_eq!(builder.n_documents, 2);
+        assert_eq!(builder.average_len, 2.5);
+        assert_eq!(builder.document_frequencies, vec![2, 1, 1]);
+        assert_eq!(builder.collection_frequencies, vec![2, 1, 2]);
+        assert_eq!(builder.document_lengths, vec![2, 3]);
+
+        let after: CsrMatrix<u32> = builder._transform(vec![count(&["hello", "world"])]);
+        assert_eq!(before, after);
+
+        // Terms pruned by fit get a column and leave the pruned terms.
+        let mut pruned = self::builder(
+            DocumentFrequency::Absolute(2),
+            DocumentFrequency::Proportion(1.0),
+            None,
+            "first_occurrence",
+        );
+        pruned
+            ._fit(vec![count(&["a", "b"]), count(&["a", "c"])])
+            .unwrap();
+        assert_eq!(pruned.pruned_terms, vec!["b", "c"]);
+        pruned._partial_fit(vec![count(&["b"])]);
+        assert_eq!(pruned.terms, vec!["a", "b"]);
+        assert_eq!(pruned.pruned_terms, vec!["c"]);
+    }
+
+    #[test]
+    fn test_fit_counts_in_chunks() {
+        let texts = vec![
+            count(&["b", "c", "c"]),
+            count(&["a", "c"]),
+            count(&["d", "a"]),
+        ];
+
+        // Chunked document lengths count pruned tokens, fitted ones only kept tokens.
+        for (min_df, max_features, lengths) in [
+            (1, None, [3, 2, 2]),
+            (2, None, [2, 2, 1]),
+            (1, Some(2), [2, 2, 1]),
+        ] {
+            let fitted = || {
+                builder(
+                    DocumentFrequency::Absolute(min_df),
+                    DocumentFrequency::Proportion(1.0),
+                    max_features,
+                    "first_occurrence",
+                )
+            };
+            let mut full = fitted();
+            full._fit(texts.clone()).unwrap();
+
+            let mut counts: TermCounts = TermCounts::new();
+            counts.update(&texts[..2]);
+            counts.update(&texts[2..]);
+            let mut chunked = fitted();
+            chunked._fit_counts(counts).unwrap();
+
+            assert_eq!(full.feature_names_out(), chunked.feature_names_out());
+            assert_eq!(full.document_frequencies(), chunked.document_frequencies());
+            assert_eq!(full.idf(), chunked.idf());
+            assert_eq!(full.average_len, chunked.average_len);
+            assert_eq!(full.document_lengths(), lengths);
+            assert_eq!(chunked.document_lengths(), [3, 2, 2]);
+            assert_eq!(chunked.n_documents, 3);
+        }
+    }
+
+    #[test]
+    fn test_library_api() {
+        let mut builder = SparseMatrixBuilder::new(SparseMatrixBuilderOptions {
+            normalize: Some(true),
+            column_order: ColumnOrder::Lexicographic,
+            ..Default::default()
+        })
+        .unwrap();
+        let texts: Vec<String> = vec!["Hello, world!".to_string(), "hello".to_string()];
+
+        let matrix: CsrMatrix<u32> = builder.fit_transform(texts.clone()).unwrap();
+        assert_eq!(builder.vocab()["world"], 1);
+        assert_eq!(matrix.indptr, vec![0, 2, 3]);
+        assert_eq!(matrix.data, vec![1, 1, 1]);
+
+        let restored = SparseMatrixBuilder::from_bytes(&builder.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.transform(texts).unwrap(), matrix);
+    }
+
+    #[test]
+    fn test_inverse_transform() {
+        let mut builder = builder(
+            DocumentFrequency::Absolute(1),
+            DocumentFrequency::Proportion(1.0),
+            None,
+            "lexicographic",
+        );
+        let texts = vec![count(&["b", "a"]), count(&[]), count(&["c"])];
+        builder._fit(texts[..1].to_vec()).unwrap();
+        builder._partial_fit(texts[2..].to_vec());
+        assert_eq!(builder.feature_names_out(), ["a", "b", "c"]);
+
+        let matrix: CsrMatrix<u32> = builder._transform(texts);
+        assert_eq!(
+            builder
+                .inverse_transform(&matrix.indptr, &matrix.indices)
+                .unwrap(),
+            vec![vec!["a", "b"], vec![], vec!["c"]]
+        );
+        assert!(builder.inverse_transform(&[0, 1], &[3]).is_err());
+        assert!(builder.inverse_transform(&[0, 2], &[0]).is_err());
+
+        let restored = SparseMatrixBuilder::from_bytes(&builder.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.feature_names_out(), builder.feature_names_out());
+    }
+
+    #[test]
+    fn test_save_load() {
+        let mut builder = builder(
+            DocumentFrequency::Absolute(2),
+            DocumentFrequency::Proportion(1.0),
+            None,
+            "frequency",
+        );
+        let texts = vec![count(&["a", "b", "b"]), count(&["b", "a", "c"])];
+        builder._fit(texts.clone()).unwrap();
+
+        let path = std::env::temp_dir().join(format!("lenlp-{}.bin", std::process::id()));
+        builder.save(&path).unwrap();
+        let loaded = SparseMatrixBuilder::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.feature_names_out(), ["b", "a"]);
+        assert_eq!(loaded.pruned_terms(), ["c"]);
+        assert_eq!(loaded.idf(), builder.idf());
+        assert_eq!(loaded.collection_frequencies(), [3, 2]);
+        assert_eq!(loaded.document_lengths(), builder.document_lengths());
+        assert_eq!(loaded.average_len, builder.average_len);
+        assert_eq!(
+            loaded._transform_bm25(texts.clone(), Bm25Params::default()),
+            builder._transform_bm25(texts, Bm25Params::default())
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&builder.to_json().unwrap()).unwrap();
+        assert_eq!(json["analyzer"], "word");
+        assert_eq!(json["column_order"], "frequency");
+        assert_eq!(json["vocabulary"], serde_json::json!(["b", "a"]));
+        assert_eq!(json["document_frequencies"], serde_json::json!([2, 2]));
+
+        assert!(SparseMatrixBuilder::load(std::env::temp_dir().join("lenlp-missing.bin")).is_err());
+    }
+
+    #[test]
+    fn test_set_document_statistics() {
+        let mut builder = default_builder();
+        let texts = vec![count(&["a", "b"]), count(&["a", "a", "c"])];
+        builder._fit(texts.clone()).unwrap();
+
+        // A builder restored with the vocabulary only weights documents the same way
+        // once its document frequencies and token count are set back.
+        let mut restored = default_builder();
+        restored
+            ._set_vocabulary(builder.feature_names_out().to_vec())
+            .unwrap();
+        restored
+            .set_document_statistics(builder.document_frequencies().to_vec(), 2, 5)
+            .unwrap();
+        assert_eq!(restored.average_len, builder.average_len);
+        assert_eq!(
+            restored._transform_bm25(texts.clone(), Bm25Params::default()),
+            builder._transform_bm25(texts, Bm25Params::default())
+        );
+        assert!(restored.set_document_statistics(vec![1], 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_set_legacy_idf() {
+        let mut builder = default_builder();
+        let texts = vec![count(&["a", "b"]), count(&["a", "a", "c"])];
+        builder._fit(texts.clone()).unwrap();
+        let idf: Vec<f32> = builder.idf().to_vec();
+
+        // The legacy idf is used for weighting and survives serialization.
+        builder.set_legacy_idf(vec![1.0, 2.0, 3.0]).unwrap();
+        let restored = SparseMatrixBuilder::from_bytes(&builder.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.idf(), [1.0, 2.0, 3.0]);
+        let matrix: CsrMatrix<f32> =
+            restored._transform_tfidf(vec![count(&["b", "c"])], false, true, None);
+        assert_eq!(matrix.data, vec![2.0, 3.0]);
+
+        // Fitting again computes the idf from the document frequencies.
+        builder._fit(texts).unwrap();
+        assert_eq!(builder.idf(), idf);
+        assert!(builder.set_legacy_idf(vec![1.0]).is_err());
+    }
+
+    #[test]
+    fn test_fixed_vocabulary() {
+        let fixed = |vocabulary: Vocabulary| {
+            SparseMatrixBuilder::new(SparseMatrixBuilderOptions {
+                min_df: DocumentFrequency::Absolute(2),
+                max_features: Some(1),
+                column_order: ColumnOrder::Lexicographic,
+                vocabulary: Some(vocabulary),
+                ..Default::default()
+            })
+        };
+        let texts = vec![count(&["z", "a", "a"]), count(&["a", "b"])];
+
+        let mut builder = fixed(Vocabulary::Mapping(HashMap::from([
+            ("z".to_string(), 0),
+            ("y".to_string(), 1),
+            ("a".to_string(), 2),
+        ])))
+        .unwrap();
+        builder._fit(texts.clone()).unwrap();
+        builder._partial_fit(vec![count(&["c", "z"])]);
+
+        // Pruning and column ordering do not apply to a fixed vocabulary.
+        assert_eq!(builder.feature_names_out(), ["z", "y", "a"]);
+        assert!(builder.pruned_terms().is_empty());
+        assert_eq!(builder.document_frequencies, vec![2, 0, 2]);
+        assert_eq!(builder.n_documents, 3);
+        assert_eq!(builder.n_tokens, 5);
+
+        let matrix: CsrMatrix<u32> = builder._transform(texts);
+        assert_eq!(matrix.indices, vec![0, 2, 2]);
+        assert_eq!(matrix.data, vec![1, 2, 1]);
+
+        let restored = SparseMatrixBuilder::from_bytes(&builder.to_bytes().unwrap()).unwrap();
+        assert!(restored.fixed_vocabulary());
+
+        let terms = fixed(Vocabulary::Terms(vec!["b".to_string(), "a".to_string()])).unwrap();
+        assert_eq!(terms.vocab()["a"], 1);
+
+        assert!(fixed(Vocabulary::Terms(vec!["a".to_string(), "a".to_string()])).is_err());
+        assert!(fixed(Vocabulary::Mapping(HashMap::from([("a".to_string(), 1)]))).is_err());
+        assert!(fixed(Vocabulary::Mapping(HashMap::from([
+            ("a".to_string(), 0),
+            ("b".to_string(), 0),
+        ])))
+        .is_err());
+    }
+
+    #[test]
+    fn test_binary() {
+        let mut builder = SparseMatrixBuilder::new(SparseMatrixBuilderOptions {
+            column_order: ColumnOrder::Lexicographic,
+            binary: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let texts: Vec<String> = vec!["a a b".to_string(), "b b b".to_string()];
+
+        let matrix: CsrMatrix<u32> = builder.fit_transform(texts.clone()).unwrap();
+        assert_eq!(matrix.data, vec![1, 1, 1]);
+        assert_eq!(builder.average_len, 1.5);
+
+        let restored = SparseMatrixBuilder::from_bytes(&builder.to_bytes().unwrap()).unwrap();
+        assert!(restored.binary());
+        let presence: CsrMatrix<u8> = restored.transform(texts).unwrap().into_binary();
+        assert_eq!(presence.indices, vec![0, 1, 1]);
+        assert_eq!(presence.data, vec![1u8, 1, 1]);
+    }
 }
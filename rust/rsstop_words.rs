@@ -1,4 +1,6 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::wrap_pyfunction;
 use rayon::prelude::*;
 use std::collections::HashSet;
@@ -13,7 +15,7 @@ use std::collections::HashSet;
 /// # Returns
 ///
 /// A string with the stop words removed.
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn rsfilter_stop_words(text: &str, stop_words: Vec<String>) -> String {
     // Use HashSet for better performance in membership checks
     let stop_words_set: HashSet<_> = stop_words.into_iter().collect();
@@ -33,7 +35,7 @@ pub fn rsfilter_stop_words(text: &str, stop_words: Vec<String>) -> String {
 /// # Returns
 ///
 /// A vector of strings with the stop words removed.
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
 pub fn rsfilter_stop_words_many(texts: Vec<String>, stop_words: Vec<String>) -> Vec<String> {
     // Use HashSet for better performance in membership checks
     let stop_words_set: HashSet<_> = stop_words.into_iter().collect();
@@ -49,6 +51,7 @@ pub fn rsfilter_stop_words_many(texts: Vec<String>, stop_words: Vec<String>) ->
         .collect()
 }
 
+#[cfg(feature = "python")]
 pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(rsfilter_stop_words, m)?)?;
     m.add_function(wrap_pyfunction!(rsfilter_stop_words_many, m)?)?;
@@ -1,12 +1,13 @@
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::wrap_pyfunction;
 use rayon::prelude::*;
 use std::collections::HashMap;
 
-use crate::rsanalyzer::rschar_ngrams;
-use crate::rsanalyzer::rschar_wb_ngrams;
-use crate::rsanalyzer::rssplit_words;
+use crate::rsanalyzer::{char_ngrams, char_wb_ngrams, split_words, validate_n_sizes, Analyzer};
 use crate::rscounter::rscount;
+use crate::rserror::LeNLPError;
 use crate::rsnormalizer::rsnormalize_many;
 use crate::rsstop_words::rsfilter_stop_words_many;
 
@@ -26,65 +27,106 @@ pub fn process_texts(
     }
 }
 
-#[pyfunction]
+/// Count the tokens of a text, or mark their presence with 1 when `binary` is set.
+fn count_tokens(tokens: Vec<String>, binary: bool) -> HashMap<String, usize> {
+    if !binary {
+        return rscount(tokens);
+    }
+    let mut presence: HashMap<String, usize> = HashMap::with_capacity(tokens.len());
+    for token in tokens {
+        presence.entry(token).or_insert(1);
+    }
+    presence
+}
+
+#[cfg_attr(feature = "python", pyfunction)]
+#[cfg_attr(
+    feature = "python",
+    pyo3(signature = (texts, n_sizes, stop_words=None, normalize=None, binary=false))
+)]
 pub fn rsvectorize_split_words_many(
     texts: Vec<String>,
     n_sizes: Vec<usize>,
     stop_words: Option<Vec<String>>,
     normalize: Option<bool>,
-) -> Vec<HashMap<String, usize>> {
+    binary: bool,
+) -> Result<Vec<HashMap<String, usize>>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
     let texts: Vec<String> = process_texts(texts, normalize, stop_words);
-    texts
+    Ok(texts
         .par_iter()
-        .map(|text: &String| rscount(rssplit_words(text, n_sizes.clone())))
-        .collect()
+        .map(|text: &String| count_tokens(split_words(text, &n_sizes), binary))
+        .collect())
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
+#[cfg_attr(
+    feature = "python",
+    pyo3(signature = (texts, n_sizes, stop_words=None, normalize=None, binary=false))
+)]
 pub fn rsvectorize_char_ngrams_many(
     texts: Vec<String>,
     n_sizes: Vec<usize>,
     stop_words: Option<Vec<String>>,
     normalize: Option<bool>,
-) -> Vec<HashMap<String, usize>> {
+    binary: bool,
+) -> Result<Vec<HashMap<String, usize>>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
     let texts: Vec<String> = process_texts(texts, normalize, stop_words);
-    texts
+    Ok(texts
         .par_iter()
-        .map(|text: &String| rscount(rschar_ngrams(text, n_sizes.clone())))
-        .collect()
+        .map(|text: &String| count_tokens(char_ngrams(text, &n_sizes), binary))
+        .collect())
 }
 
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
+#[cfg_attr(
+    feature = "python",
+    pyo3(signature = (texts, n_sizes, stop_words=None, normalize=None, binary=false))
+)]
 pub fn rsvectorize_char_wb_ngrams_many(
     texts: Vec<String>,
     n_sizes: Vec<usize>,
     stop_words: Option<Vec<String>>,
     normalize: Option<bool>,
-) -> Vec<HashMap<String, usize>> {
+    binary: bool,
+) -> Result<Vec<HashMap<String, usize>>, LeNLPError> {
+    validate_n_sizes(&n_sizes)?;
     let texts: Vec<String> = process_texts(texts, normalize, stop_words);
-    texts
+    Ok(texts
         .par_iter()
-        .map(|text: &String| rscount(rschar_wb_ngrams(text, n_sizes.clone())))
-        .collect()
+        .map(|text: &String| count_tokens(char_wb_ngrams(text, &n_sizes), binary))
+        .collect())
 }
 
 // Main vectorization function
-#[pyfunction]
+#[cfg_attr(feature = "python", pyfunction)]
+#[cfg_attr(
+    feature = "python",
+    pyo3(signature = (texts, n_sizes, analyzer, stop_words=None, normalize=None, binary=false))
+)]
 pub fn rsvectorize_many(
     texts: Vec<String>,
     n_sizes: Vec<usize>,
-    analyzer: String,
+    analyzer: Analyzer,
     stop_words: Option<Vec<String>>,
     normalize: Option<bool>,
-) -> Vec<HashMap<String, usize>> {
-    match analyzer.as_str() {
-        "word" => rsvectorize_split_words_many(texts, n_sizes, stop_words, normalize),
-        "char" => rsvectorize_char_ngrams_many(texts, n_sizes, stop_words, normalize),
-        "char_wb" => rsvectorize_char_wb_ngrams_many(texts, n_sizes, stop_words, normalize),
-        _ => panic!("Invalid analyzer type"),
+    binary: bool,
+) -> Result<Vec<HashMap<String, usize>>, LeNLPError> {
+    match analyzer {
+        Analyzer::Word => {
+            rsvectorize_split_words_many(texts, n_sizes, stop_words, normalize, binary)
+        }
+        Analyzer::Char => {
+            rsvectorize_char_ngrams_many(texts, n_sizes, stop_words, normalize, binary)
+        }
+        Analyzer::CharWb => {
+            rsvectorize_char_wb_ngrams_many(texts, n_sizes, stop_words, normalize, binary)
+        }
     }
 }
 
+#[cfg(feature = "python")]
 pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(rsvectorize_split_words_many, m)?)?;
     m.add_function(wrap_pyfunction!(rsvectorize_char_ngrams_many, m)?)?;
@@ -93,3 +135,26 @@ pub fn register_functions(m: &Bound<'_, PyModule>) -> PyResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rsvectorize_many_binary() {
+        let texts: Vec<String> = vec!["a b a".to_string()];
+        let counts = rsvectorize_many(texts.clone(), vec![1], Analyzer::Word, None, None, false);
+        assert_eq!(counts.unwrap()[0]["a"], 2);
+
+        let presence = rsvectorize_many(texts, vec![1, 2], Analyzer::Word, None, None, true);
+        assert_eq!(
+            presence.unwrap()[0],
+            HashMap::from([
+                ("a".to_string(), 1),
+                ("b".to_string(), 1),
+                ("a b".to_string(), 1),
+                ("b a".to_string(), 1),
+            ])
+        );
+    }
+}